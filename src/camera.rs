@@ -1,8 +1,9 @@
 use std::f32::consts::PI;
 
-use rand::{thread_rng, Rng};
-
-use crate::primitive::{ray::Ray, vector::Vector};
+use crate::{
+    primitive::{ray::Ray, vector::Vector},
+    sampler::Sampler,
+};
 
 /*-----------------------------------------------------------------------------------------------*/
 
@@ -56,6 +57,7 @@ impl OrbitalCamera {
         self.refresh_position();
     }
 
+    #[cfg(feature = "window")]
     pub fn delta_zoom(&mut self, delta: f32) {
         if delta < 0.0 {
             self.radius *= 1.1;
@@ -66,6 +68,7 @@ impl OrbitalCamera {
         self.refresh_position();
     }
 
+    #[cfg(feature = "window")]
     pub fn rotate(&mut self, dtheta: f32, dphi: f32) {
         self.theta += dtheta;
         self.phi += dphi;
@@ -73,10 +76,10 @@ impl OrbitalCamera {
         self.refresh_position();
     }
 
-    pub fn sample_pixel_ray(&self, pixel_offset: [i32; 2]) -> Ray {
-        let mut rng = thread_rng();
-        let radius = self.aperture * (rng.gen_range(0.0, 1.0) as f32).sqrt();
-        let theta = rng.gen_range(0.0, 2.0 * PI);
+    pub fn sample_pixel_ray(&self, pixel_offset: [i32; 2], sampler: &mut dyn Sampler) -> Ray {
+        let [aperture_random, angle_random] = sampler.get_random_pair();
+        let radius = self.aperture * aperture_random.sqrt();
+        let theta = 2.0 * PI * angle_random;
 
         let mut random_offset = self.direction_perpendiculars[0]
             .normalized_to(theta.cos())
@@ -89,11 +92,12 @@ impl OrbitalCamera {
         let perpendicular_x = direction.cross(&Vector::y());
         let perpendicular_y = perpendicular_x.cross(&direction);
 
-        let offset0 = -pixel_offset[0] as f32;
-        let offset1 = pixel_offset[1] as f32;
+        let [jitter0, jitter1] = sampler.get_random_pair();
+        let offset0 = -pixel_offset[0] as f32 + jitter0 - 0.5;
+        let offset1 = pixel_offset[1] as f32 + jitter1 - 0.5;
         let direction = direction
-            .plus(&perpendicular_x.normalized_to(self.pixel_size * rng.gen_range(offset0 - 0.5, offset0 + 0.5)))
-            .plus(&perpendicular_y.normalized_to(self.pixel_size * rng.gen_range(offset1 - 0.5, offset1 + 0.5)));
+            .plus(&perpendicular_x.normalized_to(self.pixel_size * offset0))
+            .plus(&perpendicular_y.normalized_to(self.pixel_size * offset1));
         Ray {
             origin: random_apeture_position,
             direction,
@@ -128,7 +128,10 @@ pub fn run(mut image_builder: impl TextureGenerator + 'static) {
                 void main() {
                     f_color = texture(tex, v_tex_coords);
                 }
-            "
+            ",
+
+            // The texture is already encoded by the tone mapper.
+            outputs_srgb: true,
         },)
     .unwrap();
 
@@ -1,238 +1,109 @@
+#[cfg(feature = "window")]
 #[macro_use]
 extern crate glium;
 
 mod camera;
+#[cfg(feature = "window")]
 mod display;
+mod material;
 mod object;
+mod options;
 mod primitive;
 mod raytracing;
+mod sampler;
+mod scene_file;
+#[cfg(feature = "window")]
 mod texture;
 
-use std::f32::consts::PI;
+use std::{env, process};
 
+#[cfg(feature = "window")]
 use display::run;
-use object::{cube::CubeShape, plane::PlaneShape, sphere::SphereShape, ShapeProperties, ShapeType};
-use primitive::{color::Color, vector::Vector};
-use raytracing::runner::RaytracingRunner;
+use options::{Options, BENCHMARK_RAY_COUNT, USAGE};
+use raytracing::benchmark::run_intersection_benchmark;
+use scene_file::SceneDescription;
 
 fn main() {
-    let mut scene = RaytracingRunner::new(600, 500, Vector::new(0.0, 0.0, 0.0));
-
-    scene.add_object(
-        ShapeProperties {
-            color: Color::new(1.0, 1.0, 1.0),
-            shape_type: ShapeType::Reflector {
-                transparency: 0.0,
-                roughness: 1.0,
-                density: 1.6,
-            },
-        },
-        PlaneShape::new(
-            Vector::new(0.0, 1.0, 0.0),
-            Vector::new(1.0, 0.0, 0.0),
-            PI,
-            2.0,
-            2.0,
-        ),
-    );
-
-    scene.add_object(
-        ShapeProperties {
-            color: Color::new(1.0, 1.0, 1.0),
-            shape_type: ShapeType::Reflector {
-                transparency: 0.0,
-                roughness: 1.0,
-                density: 1.6,
-            },
-        },
-        PlaneShape::new(
-            Vector::new(0.0, -1.0, 0.0),
-            Vector::new(1.0, 0.0, 0.0),
-            0.0,
-            2.0,
-            2.0,
-        ),
-    );
-
-    scene.add_object(
-        ShapeProperties {
-            color: Color::new(1.0, 1.0, 1.0),
-            shape_type: ShapeType::Reflector {
-                transparency: 0.0,
-                roughness: 1.0,
-                density: 1.6,
-            },
-        },
-        PlaneShape::new(
-            Vector::new(0.0, 0.0, 1.0),
-            Vector::new(1.0, 0.0, 0.0),
-            -PI / 2.0,
-            2.0,
-            2.0,
-        ),
-    );
-
-    scene.add_object(
-        ShapeProperties {
-            color: Color::new(1.0, 1.0, 1.0),
-            shape_type: ShapeType::Reflector {
-                transparency: 0.0,
-                roughness: 1.0,
-                density: 1.6,
-            },
-        },
-        PlaneShape::new(
-            Vector::new(0.0, 0.0, -1.0),
-            Vector::new(1.0, 0.0, 0.0),
-            PI / 2.0,
-            2.0,
-            2.0,
-        ),
-    );
-
-    scene.add_object(
-        ShapeProperties {
-            color: Color::new(1.0, 0.3, 0.3),
-            shape_type: ShapeType::Reflector {
-                transparency: 0.0,
-                roughness: 1.0,
-                density: 1.6,
-            },
-        },
-        PlaneShape::new(
-            Vector::new(1.0, 0.0, 0.0),
-            Vector::new(0.0, 0.0, 1.0),
-            PI / 2.0,
-            2.0,
-            2.0,
-        ),
-    );
-
-    scene.add_object(
-        ShapeProperties {
-            color: Color::new(0.3, 1.0, 0.3),
-            shape_type: ShapeType::Reflector {
-                transparency: 0.0,
-                roughness: 1.0,
-                density: 1.6,
-            },
-        },
-        PlaneShape::new(
-            Vector::new(-1.0, 0.0, 0.0),
-            Vector::new(0.0, 0.0, 1.0),
-            -PI / 2.0,
-            2.0,
-            2.0,
-        ),
-    );
-
-    scene.add_object(
-        ShapeProperties {
-            color: Color::new(1.0, 1.0, 1.0),
-            shape_type: ShapeType::Emitter,
-        },
-        CubeShape::new(Vector::new(0.0, 0.995, 0.0), 0.5, 0.5, 0.01),
-    );
-
-    scene.add_object(
-        ShapeProperties {
-            color: Color::new(1.0, 1.0, 1.0),
-            shape_type: ShapeType::Reflector {
-                transparency: 0.75,
-                roughness: 1.0,
-                density: 1.6,
-            },
-        },
-        SphereShape::new(Vector::new(0.6, -0.75, -0.5), 0.3),
-    );
-
-    /*
-    scene.add_object(
-        ShapeProperties {
-            color: Color::new(0.2, 1.0, 0.2),
-            shape_type: ShapeType::Reflector {
-                transparency: 1.0,
-                roughness: 1.0,
-                density: 1.6,
-            },
-        },
-        SphereShape::new(Vector::new(1.5, -0.8, -1.2), 0.2),
-    );
-
-    /*
-    scene.add_object(
-        ShapeProperties {
-            color: Color::new(0.2, 0.2, 1.0),
-            shape_type: ShapeType::Reflector {
-                transparency: 1.0,
-                roughness: 1.0,
-                density: 1.6,
-            },
-        },
-        SphereShape::new(Vector::new(1.9, -0.8, -1.2), 0.2),
-    );
-
-    scene.add_object(
-        ShapeProperties {
-            color: Color::new(1.0, 0.2, 0.2),
-            shape_type: ShapeType::Reflector {
-                transparency: 1.0,
-                roughness: 1.0,
-                density: 1.6,
-            },
-        },
-        SphereShape::new(Vector::new(1.1, -0.8, -1.2), 0.2),
-    );
-    */
-
-    scene.add_object(
-        ShapeProperties {
-            color: Color::new(1.0, 1.0, 1.0),
-            shape_type: ShapeType::Reflector {
-                transparency: 0.0,
-                roughness: 1.0,
-                density: 1.6,
-            },
-        },
-        SphereShape::new(Vector::new(1.2, -0.4, -1.2), 0.2),
-    );
-
-    scene.add_object(
-        ShapeProperties {
-            color: Color::new(0.0, 0.3, 1.0),
-            shape_type: ShapeType::Emitter,
-        },
-        SphereShape::new(Vector::new(0.0, 1.0, -2.0), 0.2),
-    );
-
-    scene.add_object(
-        ShapeProperties {
-            color: Color::new(1.0, 0.4, 0.8),
-            shape_type: ShapeType::Emitter,
-        },
-        SphereShape::new(Vector::new(1.0, 0.0, -2.0), 0.3),
-    );
-
-    scene.add_object(
-        ShapeProperties {
-            color: Color::new(1.0, 1.0, 1.0),
-            shape_type: ShapeType::Emitter,
-        },
-        SphereShape::new(Vector::new(3.0, 1.0, 0.0), 0.7),
-    );
-
-    scene.add_object(
-        ShapeProperties {
-            color: Color::new(0.5, 1.0, 1.0),
-            shape_type: ShapeType::Reflector {
-                transparency: 1.0,
-                roughness: 1.0,
-                density: 1.6,
-            },
-        },
-        CubeShape::new(Vector::new(1.5, -0.8, -1.8), 0.4, 0.4, 0.4),
-    );
-    */
-
-    run(scene);
+    let options = match Options::from_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            process::exit(1);
+        }
+    };
+
+    if let Some(sphere_count) = options.benchmark_sphere_count {
+        run_intersection_benchmark(sphere_count, BENCHMARK_RAY_COUNT, options.seed);
+        return;
+    }
+
+    let build_scene = || {
+        SceneDescription::load(&options.scene_path).and_then(|description| {
+            let mut scene = description.build_runner(options.width, options.height)?;
+            scene.set_seed(options.seed);
+            scene.set_sampler_kind(options.sampler_kind);
+            scene.set_tiling(
+                options.tile_size,
+                options.tile_order,
+                options.tile_sample_count,
+            );
+            scene.set_adaptive_sampling(options.adaptive_sampling);
+            scene.set_denoising(options.denoises);
+            scene.set_tone_mapper(options.tone_mapper);
+            Ok(scene)
+        })
+    };
+
+    let mut scene = match build_scene() {
+        Ok(scene) => scene,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            process::exit(1);
+        }
+    };
+
+    match options.headless {
+        Some(headless) => {
+            let sample_count = scene.render(headless.budget);
+            if let Err(error) = scene.save_image(&headless.output, headless.output_format) {
+                eprintln!(
+                    "error: could not write {}: {}",
+                    headless.output.display(),
+                    error
+                );
+                process::exit(1);
+            }
+
+            if let Some((path, format)) = &headless.heatmap {
+                if let Err(error) = scene.save_heatmap(path, *format) {
+                    eprintln!("error: could not write {}: {}", path.display(), error);
+                    process::exit(1);
+                }
+            }
+
+            println!(
+                "Rendered {:.1} samples per pixel to {}",
+                sample_count,
+                headless.output.display()
+            );
+
+            if headless.writes_aovs {
+                match scene.save_aovs(&headless.output, headless.output_format) {
+                    Ok(paths) => {
+                        for path in paths {
+                            println!("Wrote output variables to {}", path.display());
+                        }
+                    }
+                    Err(error) => {
+                        eprintln!("error: could not write the output variables: {}", error);
+                        process::exit(1);
+                    }
+                }
+            }
+        }
+        #[cfg(feature = "window")]
+        None => run(scene),
+        #[cfg(not(feature = "window"))]
+        None => unreachable!("builds without a window only render headless"),
+    }
 }
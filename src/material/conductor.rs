@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use crate::{
+    primitive::{color::Color, contact::RayContact, vector::Vector},
+    sampler::Sampler,
+};
+
+use super::{microfacet::GgxDistribution, texture::Texture, Material, ScatterSample};
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Rough metal, the texture's color is the reflectance at normal incidence.
+pub struct ConductorMaterial {
+    texture: Arc<dyn Texture>,
+    distribution: GgxDistribution,
+}
+
+impl ConductorMaterial {
+    pub fn new(texture: Arc<dyn Texture>, roughness: f32) -> Self {
+        Self {
+            texture,
+            distribution: GgxDistribution::from_roughness(roughness),
+        }
+    }
+}
+
+impl Material for ConductorMaterial {
+    fn scatter(&self, contact: &RayContact, sampler: &mut dyn Sampler) -> Option<ScatterSample> {
+        let normal = contact.get_facing_normal();
+        let half = self
+            .distribution
+            .sample_half_vector(&normal, sampler.get_random_pair());
+        let direction = contact.get_incoming_direction().reflected_around(&half);
+
+        let pdf = self.get_pdf(contact, &direction);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        Some(ScatterSample {
+            ray: contact.spawn_ray(direction),
+            attenuation: self.evaluate(contact, &direction).times(1.0 / pdf),
+            pdf,
+            is_specular: false,
+        })
+    }
+
+    fn evaluate(&self, contact: &RayContact, direction: &Vector) -> Color {
+        let normal = contact.get_facing_normal();
+        let outgoing = contact.get_incoming_direction().times(-1.0);
+
+        let cos_outgoing = normal.dot(&outgoing);
+        let cos_incoming = normal.dot(direction);
+        if cos_outgoing <= 0.0 || cos_incoming <= 0.0 {
+            return Color::zero();
+        }
+
+        let half = outgoing.plus(direction).normalized();
+        let specular = self.distribution.get_density(normal.dot(&half))
+            * self.distribution.get_masking(cos_outgoing, cos_incoming)
+            / (4.0 * cos_outgoing);
+
+        get_fresnel(&self.texture.get_color(contact), direction.dot(&half)).times(specular)
+    }
+
+    fn get_pdf(&self, contact: &RayContact, direction: &Vector) -> f32 {
+        let normal = contact.get_facing_normal();
+        if normal.dot(direction) <= 0.0 {
+            return 0.0;
+        }
+
+        let outgoing = contact.get_incoming_direction().times(-1.0);
+        self.distribution
+            .get_reflection_pdf(&normal, &outgoing, direction)
+    }
+
+    fn get_albedo(&self, contact: &RayContact) -> Color {
+        self.texture.get_color(contact)
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+fn get_fresnel(color: &Color, cos_theta: f32) -> Color {
+    let weight = (1.0 - cos_theta).max(0.0).powi(5);
+    Color::new(
+        color.r + (1.0 - color.r) * weight,
+        color.g + (1.0 - color.g) * weight,
+        color.b + (1.0 - color.b) * weight,
+    )
+}
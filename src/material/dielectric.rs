@@ -0,0 +1,74 @@
+use crate::{
+    primitive::{color::Color, contact::RayContact},
+    sampler::Sampler,
+};
+
+use super::{sampling::get_dielectric_reflectance, Material, ScatterSample};
+
+/*-----------------------------------------------------------------------------------------------*/
+
+/*
+ * Smooth glass. `color` filters the light each time it refracts into the object, like a thin
+ * coating, so it tints thin and thick glass alike. Glass tinted by how deep light goes through
+ * it stays white here and gets an absorbing medium instead, setting both would tint it twice.
+ */
+pub struct DielectricMaterial {
+    color: Color,
+    index_of_refraction: f32,
+}
+
+impl DielectricMaterial {
+    pub fn new(color: Color, index_of_refraction: f32) -> Self {
+        Self {
+            color,
+            index_of_refraction,
+        }
+    }
+}
+
+impl Material for DielectricMaterial {
+    fn scatter(&self, contact: &RayContact, sampler: &mut dyn Sampler) -> Option<ScatterSample> {
+        let from_inside = contact.is_from_inside();
+        let surrounding_index = contact.get_surrounding_index_of_refraction();
+        let (index_incident, index_refracted) = if from_inside {
+            (self.index_of_refraction, surrounding_index)
+        } else {
+            (surrounding_index, self.index_of_refraction)
+        };
+
+        let normal = contact.get_facing_normal();
+        let incoming = contact.get_incoming_direction();
+        let reflectance =
+            get_dielectric_reflectance(-incoming.dot(&normal), index_incident, index_refracted);
+
+        let refracted = if sampler.get_random_number() < reflectance {
+            None
+        } else {
+            incoming.refracted_through(&normal, index_incident / index_refracted)
+        };
+
+        let (direction, attenuation) = match refracted {
+            Some(direction) if from_inside => (direction, Color::new(1.0, 1.0, 1.0)),
+            Some(direction) => (direction, self.color),
+            None => (
+                incoming.reflected_around(&normal),
+                Color::new(1.0, 1.0, 1.0),
+            ),
+        };
+
+        Some(ScatterSample {
+            ray: contact.spawn_ray(direction),
+            attenuation,
+            pdf: 1.0,
+            is_specular: true,
+        })
+    }
+
+    fn get_index_of_refraction(&self) -> Option<f32> {
+        Some(self.index_of_refraction)
+    }
+
+    fn get_albedo(&self, _contact: &RayContact) -> Color {
+        self.color
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{
+    primitive::{color::Color, contact::RayContact},
+    sampler::Sampler,
+};
+
+use super::{Material, ScatterSample};
+
+/*-----------------------------------------------------------------------------------------------*/
+
+pub struct EmissiveMaterial {
+    radiance: Color,
+}
+
+impl EmissiveMaterial {
+    pub fn new(color: Color, intensity: f32) -> Self {
+        Self {
+            radiance: color.times(intensity),
+        }
+    }
+}
+
+impl Material for EmissiveMaterial {
+    fn scatter(&self, _contact: &RayContact, _sampler: &mut dyn Sampler) -> Option<ScatterSample> {
+        None
+    }
+
+    fn get_emission(&self, _contact: &RayContact) -> Color {
+        self.radiance
+    }
+
+    fn is_emissive(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,31 @@
+use crate::{
+    primitive::{color::Color, contact::RayContact},
+    sampler::Sampler,
+};
+
+use super::{Material, ScatterSample};
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Surface that light passes straight through, the bounds of a medium such as fog that has no
+// surface of its own.
+pub struct InvisibleMaterial;
+
+impl Material for InvisibleMaterial {
+    fn scatter(&self, contact: &RayContact, _sampler: &mut dyn Sampler) -> Option<ScatterSample> {
+        Some(ScatterSample {
+            ray: contact.spawn_ray(contact.get_incoming_direction()),
+            attenuation: Color::new(1.0, 1.0, 1.0),
+            pdf: 1.0,
+            is_specular: true,
+        })
+    }
+
+    fn is_transmissive(&self) -> bool {
+        true
+    }
+
+    fn is_invisible(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use crate::{
+    primitive::{color::Color, contact::RayContact, vector::Vector},
+    sampler::Sampler,
+};
+
+use super::{
+    sampling::{get_cosine_hemisphere_pdf, sample_cosine_hemisphere},
+    texture::Texture,
+    Material, ScatterSample,
+};
+
+/*-----------------------------------------------------------------------------------------------*/
+
+pub struct LambertianMaterial {
+    texture: Arc<dyn Texture>,
+}
+
+impl LambertianMaterial {
+    pub fn new(texture: Arc<dyn Texture>) -> Self {
+        Self { texture }
+    }
+}
+
+impl Material for LambertianMaterial {
+    fn scatter(&self, contact: &RayContact, sampler: &mut dyn Sampler) -> Option<ScatterSample> {
+        let normal = contact.get_facing_normal();
+        let direction = sample_cosine_hemisphere(&normal, sampler.get_random_pair());
+
+        Some(ScatterSample {
+            ray: contact.spawn_ray(direction),
+            attenuation: self.texture.get_color(contact),
+            pdf: get_cosine_hemisphere_pdf(direction.dot(&normal)),
+            is_specular: false,
+        })
+    }
+
+    fn evaluate(&self, contact: &RayContact, direction: &Vector) -> Color {
+        self.texture
+            .get_color(contact)
+            .times(get_cosine_hemisphere_pdf(
+                direction.dot(&contact.get_facing_normal()),
+            ))
+    }
+
+    fn get_pdf(&self, contact: &RayContact, direction: &Vector) -> f32 {
+        get_cosine_hemisphere_pdf(direction.dot(&contact.get_facing_normal()))
+    }
+
+    fn get_albedo(&self, contact: &RayContact) -> Color {
+        self.texture.get_color(contact)
+    }
+}
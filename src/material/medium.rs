@@ -0,0 +1,185 @@
+use std::f32::consts::PI;
+
+use crate::{
+    primitive::{color::Color, contact::RayContact, vector::Vector},
+    sampler::Sampler,
+};
+
+use super::{sampling::to_world, Material, ScatterSample};
+
+/*-----------------------------------------------------------------------------------------------*/
+
+/*
+ * Homogeneous participating medium such as tinted glass, fog or smoke. Both coefficients are per
+ * unit of distance: light is absorbed along the way following Beer–Lambert's law, and scattered
+ * into new directions picked by a Henyey–Greenstein phase function of the given asymmetry.
+ */
+#[derive(Clone, Copy)]
+pub struct Medium {
+    absorption: Color,
+    scattering: Color,
+    asymmetry: f32,
+}
+
+// What happened to a ray over a stretch of medium, with the weight to filter its throughput by.
+pub struct MediumInteraction {
+    pub weight: Color,
+    // Distance into the stretch at which the ray scattered, `None` when it made it through.
+    pub scatter_distance: Option<f32>,
+}
+
+impl Medium {
+    pub fn new(absorption: Color, scattering: Color, asymmetry: f32) -> Self {
+        Self {
+            absorption,
+            scattering,
+            asymmetry,
+        }
+    }
+
+    pub fn get_extinction(&self) -> Color {
+        self.absorption.plus(&self.scattering)
+    }
+
+    pub fn get_transmittance(&self, distance: f32) -> Color {
+        let extinction = self.get_extinction();
+        Color::new(
+            get_channel_transmittance(extinction.r, distance),
+            get_channel_transmittance(extinction.g, distance),
+            get_channel_transmittance(extinction.b, distance),
+        )
+    }
+
+    pub fn get_phase_function(&self) -> HenyeyGreenstein {
+        HenyeyGreenstein::new(self.asymmetry)
+    }
+
+    /*
+     * Samples where a ray crossing `length` of the medium first scatters. The distance follows the
+     * extinction of one channel picked at random, and the weight divides by the average density
+     * of the three so that colored media stay unbiased. Media that only absorb never scatter, they
+     * just dim the ray.
+     */
+    pub fn sample_interaction(&self, length: f32, sampler: &mut dyn Sampler) -> MediumInteraction {
+        if self.scattering.get_max_component() <= 0.0 {
+            return MediumInteraction {
+                weight: self.get_transmittance(length),
+                scatter_distance: None,
+            };
+        }
+
+        let extinction = self.get_extinction();
+        let channel_extinction = match (sampler.get_random_number() * 3.0) as usize {
+            0 => extinction.r,
+            1 => extinction.g,
+            _ => extinction.b,
+        };
+        let distance = if channel_extinction > 0.0 {
+            -(1.0 - sampler.get_random_number()).ln() / channel_extinction
+        } else {
+            f32::INFINITY
+        };
+
+        if distance < length {
+            let transmittance = self.get_transmittance(distance);
+            let pdf = (extinction.r * transmittance.r
+                + extinction.g * transmittance.g
+                + extinction.b * transmittance.b)
+                / 3.0;
+            MediumInteraction {
+                weight: get_weight(transmittance.filter(&self.scattering), pdf),
+                scatter_distance: Some(distance),
+            }
+        } else {
+            let transmittance = self.get_transmittance(length);
+            let pdf = (transmittance.r + transmittance.g + transmittance.b) / 3.0;
+            MediumInteraction {
+                weight: get_weight(transmittance, pdf),
+                scatter_distance: None,
+            }
+        }
+    }
+}
+
+fn get_channel_transmittance(extinction: f32, distance: f32) -> f32 {
+    if extinction > 0.0 {
+        (-extinction * distance).exp()
+    } else {
+        1.0
+    }
+}
+
+fn get_weight(value: Color, pdf: f32) -> Color {
+    if pdf > 0.0 {
+        value.times(1.0 / pdf)
+    } else {
+        Color::zero()
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+/*
+ * Phase function of a medium, scattering at points inside it the way a material does at a
+ * surface. Positive asymmetries send light mostly onwards, negative ones mostly back, and zero
+ * scatters it evenly in every direction.
+ */
+pub struct HenyeyGreenstein {
+    asymmetry: f32,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(asymmetry: f32) -> Self {
+        Self { asymmetry }
+    }
+
+    // `cos_angle` is between the ray and the direction the light comes from, 1 when it goes on
+    // straight through.
+    fn get_phase(&self, cos_angle: f32) -> f32 {
+        let g = self.asymmetry;
+        let denominator = 1.0 + g * g - 2.0 * g * cos_angle;
+        (1.0 - g * g) / (4.0 * PI * denominator * denominator.max(0.0).sqrt())
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    fn scatter(&self, contact: &RayContact, sampler: &mut dyn Sampler) -> Option<ScatterSample> {
+        let g = self.asymmetry;
+        let [u, v] = sampler.get_random_pair();
+        let cos_angle = if g.abs() < 1e-3 {
+            1.0 - 2.0 * u
+        } else {
+            let term = (1.0 - g * g) / (1.0 - g + 2.0 * g * u);
+            ((1.0 + g * g - term * term) / (2.0 * g)).clamp(-1.0, 1.0)
+        };
+        let sin_angle = (1.0 - cos_angle * cos_angle).max(0.0).sqrt();
+        let phi = 2.0 * PI * v;
+
+        let incoming = contact.get_incoming_direction();
+        let direction = to_world(
+            &incoming,
+            &Vector::new(sin_angle * phi.cos(), sin_angle * phi.sin(), cos_angle),
+        );
+
+        Some(ScatterSample {
+            ray: contact.spawn_ray(direction),
+            // The phase function is sampled exactly, so it cancels out with its pdf.
+            attenuation: Color::new(1.0, 1.0, 1.0),
+            pdf: self.get_phase(cos_angle),
+            is_specular: false,
+        })
+    }
+
+    fn evaluate(&self, contact: &RayContact, direction: &Vector) -> Color {
+        let phase = self.get_pdf(contact, direction);
+        Color::new(phase, phase, phase)
+    }
+
+    fn get_pdf(&self, contact: &RayContact, direction: &Vector) -> f32 {
+        self.get_phase(contact.get_incoming_direction().dot(direction))
+    }
+
+    fn is_transmissive(&self) -> bool {
+        true
+    }
+}
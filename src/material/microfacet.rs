@@ -0,0 +1,69 @@
+use std::f32::consts::PI;
+
+use crate::primitive::vector::Vector;
+
+use super::sampling::to_world;
+
+const MIN_ALPHA: f32 = 0.001;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// GGX (Trowbridge-Reitz) distribution with the Smith masking-shadowing term.
+pub struct GgxDistribution {
+    alpha: f32,
+}
+
+impl GgxDistribution {
+    pub fn from_roughness(roughness: f32) -> Self {
+        Self {
+            alpha: (roughness * roughness).max(MIN_ALPHA),
+        }
+    }
+
+    pub fn get_density(&self, cos_half: f32) -> f32 {
+        if cos_half <= 0.0 {
+            return 0.0;
+        }
+
+        let alpha_sqr = self.alpha * self.alpha;
+        let denominator = cos_half * cos_half * (alpha_sqr - 1.0) + 1.0;
+        alpha_sqr / (PI * denominator * denominator)
+    }
+
+    pub fn get_masking(&self, cos_outgoing: f32, cos_incoming: f32) -> f32 {
+        self.get_single_masking(cos_outgoing) * self.get_single_masking(cos_incoming)
+    }
+
+    pub fn sample_half_vector(&self, normal: &Vector, random: [f32; 2]) -> Vector {
+        let alpha_sqr = self.alpha * self.alpha;
+        let cos_theta = ((1.0 - random[0]) / (1.0 + (alpha_sqr - 1.0) * random[0])).sqrt();
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * random[1];
+
+        to_world(
+            normal,
+            &Vector::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta),
+        )
+    }
+
+    // Density of reflected directions when half vectors are sampled with `sample_half_vector`.
+    pub fn get_reflection_pdf(&self, normal: &Vector, outgoing: &Vector, incoming: &Vector) -> f32 {
+        let half = outgoing.plus(incoming).normalized();
+        let outgoing_dot_half = outgoing.dot(&half).abs();
+        if outgoing_dot_half <= 0.0 {
+            return 0.0;
+        }
+
+        self.get_density(normal.dot(&half)) * normal.dot(&half) / (4.0 * outgoing_dot_half)
+    }
+
+    fn get_single_masking(&self, cos_theta: f32) -> f32 {
+        if cos_theta <= 0.0 {
+            return 0.0;
+        }
+
+        let cos_sqr = cos_theta * cos_theta;
+        let tan_sqr = (1.0 - cos_sqr) / cos_sqr;
+        2.0 / (1.0 + (1.0 + self.alpha * self.alpha * tan_sqr).sqrt())
+    }
+}
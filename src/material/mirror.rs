@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use crate::{
+    primitive::{color::Color, contact::RayContact},
+    sampler::Sampler,
+};
+
+use super::{texture::Texture, Material, ScatterSample};
+
+/*-----------------------------------------------------------------------------------------------*/
+
+pub struct MirrorMaterial {
+    texture: Arc<dyn Texture>,
+}
+
+impl MirrorMaterial {
+    pub fn new(texture: Arc<dyn Texture>) -> Self {
+        Self { texture }
+    }
+}
+
+impl Material for MirrorMaterial {
+    fn scatter(&self, contact: &RayContact, _sampler: &mut dyn Sampler) -> Option<ScatterSample> {
+        let direction = contact
+            .get_incoming_direction()
+            .reflected_around(&contact.get_facing_normal());
+
+        Some(ScatterSample {
+            ray: contact.spawn_ray(direction),
+            attenuation: self.texture.get_color(contact),
+            pdf: 1.0,
+            is_specular: true,
+        })
+    }
+
+    fn get_albedo(&self, contact: &RayContact) -> Color {
+        self.texture.get_color(contact)
+    }
+}
@@ -0,0 +1,77 @@
+pub mod conductor;
+pub mod dielectric;
+pub mod emissive;
+pub mod invisible;
+pub mod lambertian;
+pub mod medium;
+mod microfacet;
+pub mod mirror;
+pub mod normal_map;
+pub mod plastic;
+pub mod sampling;
+pub mod texture;
+
+use crate::{
+    primitive::{color::Color, contact::RayContact, ray::Ray, vector::Vector},
+    sampler::Sampler,
+};
+
+/*-----------------------------------------------------------------------------------------------*/
+
+pub struct ScatterSample {
+    pub ray: Ray,
+    // BSDF times the cosine term, divided by the pdf of the sampled direction.
+    pub attenuation: Color,
+    pub pdf: f32,
+    pub is_specular: bool,
+}
+
+/*
+ * Directions given to `evaluate` and `get_pdf` point away from the contact, towards where the
+ * light comes from. The direction the light leaves towards is the reverse of the contact's ray.
+ */
+pub trait Material: Sync + Send {
+    fn scatter(&self, contact: &RayContact, sampler: &mut dyn Sampler) -> Option<ScatterSample>;
+
+    fn evaluate(&self, _contact: &RayContact, _direction: &Vector) -> Color {
+        Color::zero()
+    }
+
+    fn get_pdf(&self, _contact: &RayContact, _direction: &Vector) -> f32 {
+        0.0
+    }
+
+    fn get_emission(&self, _contact: &RayContact) -> Color {
+        Color::zero()
+    }
+
+    // Surface color at the contact, the guide the denoiser uses to tell texture from noise.
+    fn get_albedo(&self, _contact: &RayContact) -> Color {
+        Color::new(1.0, 1.0, 1.0)
+    }
+
+    // Emissive materials on shapes with a surface area are sampled directly as lights.
+    fn is_emissive(&self) -> bool {
+        false
+    }
+
+    // Materials with an index of refraction bound a medium that transmitted rays enter or leave.
+    fn get_index_of_refraction(&self) -> Option<f32> {
+        None
+    }
+
+    // Materials that let light through, the others block rays that would cross their surface.
+    fn is_transmissive(&self) -> bool {
+        self.get_index_of_refraction().is_some()
+    }
+
+    // Invisible surfaces only bound a medium, shadow rays go through them.
+    fn is_invisible(&self) -> bool {
+        false
+    }
+
+    // Normal to scatter around in place of the surface's own, set on the contact by the scene.
+    fn get_shading_normal(&self, _contact: &RayContact) -> Option<Vector> {
+        None
+    }
+}
@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use crate::{
+    primitive::{color::Color, contact::RayContact, vector::Vector},
+    sampler::Sampler,
+};
+
+use super::{texture::Texture, Material, ScatterSample};
+
+// Step along the surface over which bump map heights are differentiated.
+const BUMP_STEP: f32 = 1e-3;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Bends the shading normal to add detail the geometry doesn't have.
+pub trait NormalMap: Sync + Send {
+    fn get_normal(&self, contact: &RayContact) -> Vector;
+}
+
+/*
+ * Normals stored as colors in the surface's own frame: red along the tangent, green along the
+ * bitangent and blue out of the surface, each mapped from [-1, 1] to [0, 1]. `strength` scales
+ * the tilt, 0 leaves the surface flat.
+ */
+pub struct TangentNormalMap {
+    texture: Arc<dyn Texture>,
+    strength: f32,
+}
+
+impl TangentNormalMap {
+    pub fn new(texture: Arc<dyn Texture>, strength: f32) -> Self {
+        Self { texture, strength }
+    }
+}
+
+impl NormalMap for TangentNormalMap {
+    fn get_normal(&self, contact: &RayContact) -> Vector {
+        let color = self.texture.get_color(contact);
+        let [tangent, bitangent] = contact.get_tangents();
+
+        tangent
+            .times(self.strength * (2.0 * color.r - 1.0))
+            .plus(&bitangent.times(self.strength * (2.0 * color.g - 1.0)))
+            .plus(&contact.get_shading_normal().times(2.0 * color.b - 1.0))
+            .normalized()
+    }
+}
+
+/*
+ * Heights from the brightness of a texture, raised `strength` times along the normal. The normal
+ * leans away from where they grow, found by looking the texture up a small step further along
+ * each tangent. The step moves both the surface coordinates and the position, so textures looked
+ * up either way have a slope.
+ */
+pub struct BumpMap {
+    texture: Arc<dyn Texture>,
+    strength: f32,
+}
+
+impl BumpMap {
+    pub fn new(texture: Arc<dyn Texture>, strength: f32) -> Self {
+        Self { texture, strength }
+    }
+
+    fn get_height(&self, contact: &RayContact) -> f32 {
+        self.strength * self.texture.get_color(contact).get_luminance()
+    }
+}
+
+impl NormalMap for BumpMap {
+    fn get_normal(&self, contact: &RayContact) -> Vector {
+        let [tangent, bitangent] = contact.get_tangents();
+        let height = self.get_height(contact);
+        let slope_u = (self
+            .get_height(&contact.shifted(&tangent.times(BUMP_STEP), [BUMP_STEP, 0.0]))
+            - height)
+            / BUMP_STEP;
+        let slope_v = (self
+            .get_height(&contact.shifted(&bitangent.times(BUMP_STEP), [0.0, BUMP_STEP]))
+            - height)
+            / BUMP_STEP;
+
+        contact
+            .get_shading_normal()
+            .minus(&tangent.times(slope_u))
+            .minus(&bitangent.times(slope_v))
+            .normalized()
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+/*
+ * Material with its shading normal bent by a normal map. The scene applies the normal to the
+ * contact before the material sees it, so the wrapped material scatters around it unchanged.
+ */
+pub struct NormalMappedMaterial {
+    material: Arc<dyn Material>,
+    normal_map: Arc<dyn NormalMap>,
+}
+
+impl NormalMappedMaterial {
+    pub fn new(material: Arc<dyn Material>, normal_map: Arc<dyn NormalMap>) -> Self {
+        Self {
+            material,
+            normal_map,
+        }
+    }
+}
+
+impl Material for NormalMappedMaterial {
+    fn scatter(&self, contact: &RayContact, sampler: &mut dyn Sampler) -> Option<ScatterSample> {
+        self.material.scatter(contact, sampler)
+    }
+
+    fn evaluate(&self, contact: &RayContact, direction: &Vector) -> Color {
+        self.material.evaluate(contact, direction)
+    }
+
+    fn get_pdf(&self, contact: &RayContact, direction: &Vector) -> f32 {
+        self.material.get_pdf(contact, direction)
+    }
+
+    fn get_emission(&self, contact: &RayContact) -> Color {
+        self.material.get_emission(contact)
+    }
+
+    fn get_albedo(&self, contact: &RayContact) -> Color {
+        self.material.get_albedo(contact)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn get_index_of_refraction(&self) -> Option<f32> {
+        self.material.get_index_of_refraction()
+    }
+
+    fn is_transmissive(&self) -> bool {
+        self.material.is_transmissive()
+    }
+
+    fn is_invisible(&self) -> bool {
+        self.material.is_invisible()
+    }
+
+    fn get_shading_normal(&self, contact: &RayContact) -> Option<Vector> {
+        Some(self.normal_map.get_normal(contact))
+    }
+}
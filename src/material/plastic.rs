@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use crate::{
+    primitive::{color::Color, contact::RayContact, vector::Vector},
+    sampler::Sampler,
+};
+
+use super::{
+    microfacet::GgxDistribution,
+    sampling::{get_cosine_hemisphere_pdf, get_schlick_reflectance, sample_cosine_hemisphere},
+    texture::Texture,
+    Material, ScatterSample,
+};
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Diffuse base under a rough, uncolored dielectric coating.
+pub struct PlasticMaterial {
+    texture: Arc<dyn Texture>,
+    distribution: GgxDistribution,
+    normal_reflectance: f32,
+}
+
+impl PlasticMaterial {
+    pub fn new(texture: Arc<dyn Texture>, roughness: f32, index_of_refraction: f32) -> Self {
+        let reflectance_root = (index_of_refraction - 1.0) / (index_of_refraction + 1.0);
+        Self {
+            texture,
+            distribution: GgxDistribution::from_roughness(roughness),
+            normal_reflectance: reflectance_root * reflectance_root,
+        }
+    }
+
+    fn get_specular_probability(&self, contact: &RayContact) -> f32 {
+        let cos_outgoing = -contact
+            .get_incoming_direction()
+            .dot(&contact.get_facing_normal());
+        get_schlick_reflectance(self.normal_reflectance, cos_outgoing)
+    }
+}
+
+impl Material for PlasticMaterial {
+    fn scatter(&self, contact: &RayContact, sampler: &mut dyn Sampler) -> Option<ScatterSample> {
+        let normal = contact.get_facing_normal();
+        let direction = if sampler.get_random_number() < self.get_specular_probability(contact) {
+            let half = self
+                .distribution
+                .sample_half_vector(&normal, sampler.get_random_pair());
+            contact.get_incoming_direction().reflected_around(&half)
+        } else {
+            sample_cosine_hemisphere(&normal, sampler.get_random_pair())
+        };
+
+        let pdf = self.get_pdf(contact, &direction);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        Some(ScatterSample {
+            ray: contact.spawn_ray(direction),
+            attenuation: self.evaluate(contact, &direction).times(1.0 / pdf),
+            pdf,
+            is_specular: false,
+        })
+    }
+
+    fn evaluate(&self, contact: &RayContact, direction: &Vector) -> Color {
+        let normal = contact.get_facing_normal();
+        let outgoing = contact.get_incoming_direction().times(-1.0);
+
+        let cos_outgoing = normal.dot(&outgoing);
+        let cos_incoming = normal.dot(direction);
+        if cos_outgoing <= 0.0 || cos_incoming <= 0.0 {
+            return Color::zero();
+        }
+
+        let half = outgoing.plus(direction).normalized();
+        let specular = get_schlick_reflectance(self.normal_reflectance, direction.dot(&half))
+            * self.distribution.get_density(normal.dot(&half))
+            * self.distribution.get_masking(cos_outgoing, cos_incoming)
+            / (4.0 * cos_outgoing);
+
+        let diffuse_weight = 1.0 - get_schlick_reflectance(self.normal_reflectance, cos_outgoing);
+        self.texture
+            .get_color(contact)
+            .times(diffuse_weight * get_cosine_hemisphere_pdf(cos_incoming))
+            .plus(&Color::new(specular, specular, specular))
+    }
+
+    fn get_pdf(&self, contact: &RayContact, direction: &Vector) -> f32 {
+        let normal = contact.get_facing_normal();
+        let cos_incoming = normal.dot(direction);
+        if cos_incoming <= 0.0 {
+            return 0.0;
+        }
+
+        let outgoing = contact.get_incoming_direction().times(-1.0);
+        let specular_probability = self.get_specular_probability(contact);
+
+        specular_probability
+            * self
+                .distribution
+                .get_reflection_pdf(&normal, &outgoing, direction)
+            + (1.0 - specular_probability) * get_cosine_hemisphere_pdf(cos_incoming)
+    }
+
+    fn get_albedo(&self, contact: &RayContact) -> Color {
+        self.texture.get_color(contact)
+    }
+}
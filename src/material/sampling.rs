@@ -0,0 +1,63 @@
+use std::f32::consts::PI;
+
+use crate::primitive::vector::Vector;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+pub fn to_world(normal: &Vector, local: &Vector) -> Vector {
+    let [tangent, bitangent] = normal.get_perpendiculars();
+    tangent
+        .times(local.x)
+        .plus(&bitangent.times(local.y))
+        .plus(&normal.times(local.z))
+}
+
+pub fn sample_cosine_hemisphere(normal: &Vector, random: [f32; 2]) -> Vector {
+    let radius = random[0].sqrt();
+    let phi = 2.0 * PI * random[1];
+    let local = Vector::new(
+        radius * phi.cos(),
+        radius * phi.sin(),
+        (1.0 - random[0]).max(0.0).sqrt(),
+    );
+
+    to_world(normal, &local)
+}
+
+pub fn sample_uniform_sphere(random: [f32; 2]) -> Vector {
+    let z = 1.0 - 2.0 * random[0];
+    let radius = (1.0 - z * z).max(0.0).sqrt();
+    let phi = 2.0 * PI * random[1];
+
+    Vector::new(radius * phi.cos(), radius * phi.sin(), z)
+}
+
+pub fn get_cosine_hemisphere_pdf(cos_theta: f32) -> f32 {
+    cos_theta.max(0.0) / PI
+}
+
+pub fn get_schlick_reflectance(normal_reflectance: f32, cos_theta: f32) -> f32 {
+    normal_reflectance + (1.0 - normal_reflectance) * (1.0 - cos_theta).max(0.0).powi(5)
+}
+
+// Unpolarized Fresnel reflectance of a smooth dielectric interface, 1 on total internal reflection.
+pub fn get_dielectric_reflectance(
+    cos_incident: f32,
+    index_incident: f32,
+    index_refracted: f32,
+) -> f32 {
+    let cos_incident = cos_incident.clamp(0.0, 1.0);
+    let sin_refracted =
+        (index_incident / index_refracted) * (1.0 - cos_incident * cos_incident).sqrt();
+    if sin_refracted >= 1.0 {
+        return 1.0;
+    }
+
+    let cos_refracted = (1.0 - sin_refracted * sin_refracted).sqrt();
+    let perpendicular = (index_incident * cos_incident - index_refracted * cos_refracted)
+        / (index_incident * cos_incident + index_refracted * cos_refracted);
+    let parallel = (index_refracted * cos_incident - index_incident * cos_refracted)
+        / (index_refracted * cos_incident + index_incident * cos_refracted);
+
+    0.5 * (perpendicular * perpendicular + parallel * parallel)
+}
@@ -0,0 +1,277 @@
+use std::{io, path::Path};
+
+use serde::Deserialize;
+
+use crate::primitive::{color::Color, contact::RayContact, vector::Vector};
+
+// Noise sums octaves of twice the frequency and half the amplitude, more than this add nothing.
+const MAX_NOISE_OCTAVE_COUNT: u32 = 8;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Color that varies over a surface, looked up by the contact's surface coordinates or position.
+pub trait Texture: Sync + Send {
+    fn get_color(&self, contact: &RayContact) -> Color;
+}
+
+impl Texture for Color {
+    fn get_color(&self, _contact: &RayContact) -> Color {
+        *self
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Squares of two alternating colors, `scale` of them along each unit of the surface coordinates.
+pub struct CheckerTexture {
+    colors: [Color; 2],
+    scale: f32,
+}
+
+impl CheckerTexture {
+    pub fn new(even: Color, odd: Color, scale: f32) -> Self {
+        Self {
+            colors: [even, odd],
+            scale,
+        }
+    }
+}
+
+impl Texture for CheckerTexture {
+    fn get_color(&self, contact: &RayContact) -> Color {
+        let [u, v] = contact.get_uv();
+        let parity = (u * self.scale).floor() as i64 + (v * self.scale).floor() as i64;
+        self.colors[parity.rem_euclid(2) as usize]
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Blend from `start` at the bottom of the surface coordinates, v = 0, to `end` at the top.
+pub struct GradientTexture {
+    start: Color,
+    end: Color,
+}
+
+impl GradientTexture {
+    pub fn new(start: Color, end: Color) -> Self {
+        Self { start, end }
+    }
+}
+
+impl Texture for GradientTexture {
+    fn get_color(&self, contact: &RayContact) -> Color {
+        let weight = contact.get_uv()[1].clamp(0.0, 1.0);
+        self.start.times(1.0 - weight).plus(&self.end.times(weight))
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+/*
+ * Perlin noise blending between two colors. It's evaluated at the contact's position rather than
+ * its surface coordinates, so the pattern runs through objects like grain through marble and
+ * doesn't stretch over shapes that have none.
+ */
+pub struct NoiseTexture {
+    colors: [Color; 2],
+    scale: f32,
+    octave_count: u32,
+}
+
+impl NoiseTexture {
+    pub fn new(low: Color, high: Color, scale: f32, octave_count: u32) -> Self {
+        Self {
+            colors: [low, high],
+            scale,
+            octave_count: octave_count.clamp(1, MAX_NOISE_OCTAVE_COUNT),
+        }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn get_color(&self, contact: &RayContact) -> Color {
+        let mut point = contact.get_position().times(self.scale);
+        let mut amplitude = 1.0;
+        let mut sum = 0.0;
+        let mut amplitude_sum = 0.0;
+        for _ in 0..self.octave_count {
+            sum += amplitude * get_perlin_noise(&point);
+            amplitude_sum += amplitude;
+            amplitude *= 0.5;
+            point = point.times(2.0);
+        }
+
+        let weight = (0.5 + 0.5 * sum / amplitude_sum).clamp(0.0, 1.0);
+        self.colors[0]
+            .times(1.0 - weight)
+            .plus(&self.colors[1].times(weight))
+    }
+}
+
+// Improved Perlin noise in about [-1, 1], with the lattice gradients picked by hashing the corners.
+fn get_perlin_noise(point: &Vector) -> f32 {
+    let cell = [point.x.floor(), point.y.floor(), point.z.floor()];
+    let offset = [point.x - cell[0], point.y - cell[1], point.z - cell[2]];
+    let cell = [cell[0] as i32, cell[1] as i32, cell[2] as i32];
+    let fade = offset.map(|t| t * t * t * (t * (t * 6.0 - 15.0) + 10.0));
+
+    let mut corners = [0.0; 8];
+    for (corner, value) in corners.iter_mut().enumerate() {
+        let step = [corner & 1, (corner >> 1) & 1, (corner >> 2) & 1];
+        let hash = hash_lattice_point([
+            cell[0].wrapping_add(step[0] as i32),
+            cell[1].wrapping_add(step[1] as i32),
+            cell[2].wrapping_add(step[2] as i32),
+        ]);
+        let to_point = [
+            offset[0] - step[0] as f32,
+            offset[1] - step[1] as f32,
+            offset[2] - step[2] as f32,
+        ];
+        *value = get_gradient_dot(hash, to_point);
+    }
+
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+    let x = [
+        lerp(corners[0], corners[1], fade[0]),
+        lerp(corners[2], corners[3], fade[0]),
+        lerp(corners[4], corners[5], fade[0]),
+        lerp(corners[6], corners[7], fade[0]),
+    ];
+    let y = [lerp(x[0], x[1], fade[1]), lerp(x[2], x[3], fade[1])];
+
+    lerp(y[0], y[1], fade[2])
+}
+
+fn hash_lattice_point(point: [i32; 3]) -> u32 {
+    let mut hash = (point[0] as u32).wrapping_mul(0x8da6_b343)
+        ^ (point[1] as u32).wrapping_mul(0xd816_3841)
+        ^ (point[2] as u32).wrapping_mul(0xcb1a_b31f);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2c1b_3c6d);
+    hash ^ (hash >> 12)
+}
+
+// Dot product with one of the twelve directions towards the edges of a cube, as Perlin does.
+fn get_gradient_dot(hash: u32, [x, y, z]: [f32; 3]) -> f32 {
+    match hash % 12 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x + z,
+        5 => -x + z,
+        6 => x - z,
+        7 => -x - z,
+        8 => y + z,
+        9 => -y + z,
+        10 => y - z,
+        _ => -y - z,
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// What image lookups do outside [0, 1).
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum WrapMode {
+    Repeat,
+    Mirror,
+    Clamp,
+}
+
+impl WrapMode {
+    fn apply(self, index: i64, size: u32) -> usize {
+        let size = size as i64;
+        let index = match self {
+            WrapMode::Repeat => index.rem_euclid(size),
+            WrapMode::Mirror => {
+                let period = index.rem_euclid(2 * size);
+                if period < size {
+                    period
+                } else {
+                    2 * size - 1 - period
+                }
+            }
+            WrapMode::Clamp => index.clamp(0, size - 1),
+        };
+
+        index as usize
+    }
+}
+
+// PNG or JPEG image stretched over the surface coordinates, filtered between its four nearest
+// pixels. Images of data rather than colors, as normal maps, are read without sRGB decoding.
+pub struct ImageTexture {
+    width: u32,
+    height: u32,
+    // Linear colors, top row first.
+    data: Vec<Color>,
+    wrap_mode: WrapMode,
+}
+
+impl ImageTexture {
+    pub fn load(path: &Path, wrap_mode: WrapMode, is_srgb: bool) -> io::Result<Self> {
+        let image = image::open(path)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?
+            .to_rgb8();
+
+        // 8-bit color images are stored sRGB encoded, lighting needs the linear values.
+        let decode: fn(u8) -> f32 = if is_srgb {
+            decode_srgb
+        } else {
+            |value: u8| value as f32 / 255.0
+        };
+        let data = image
+            .pixels()
+            .map(|pixel| Color::new(decode(pixel[0]), decode(pixel[1]), decode(pixel[2])))
+            .collect();
+
+        Ok(Self {
+            width: image.width(),
+            height: image.height(),
+            data,
+            wrap_mode,
+        })
+    }
+
+    fn get_pixel(&self, x: i64, y: i64) -> Color {
+        let x = self.wrap_mode.apply(x, self.width);
+        let y = self.wrap_mode.apply(y, self.height);
+        self.data[y * self.width as usize + x]
+    }
+}
+
+impl Texture for ImageTexture {
+    fn get_color(&self, contact: &RayContact) -> Color {
+        // v grows upwards while the rows go down, pixel centers sit half a pixel in.
+        let [u, v] = contact.get_uv();
+        let x = u * self.width as f32 - 0.5;
+        let y = (1.0 - v) * self.height as f32 - 0.5;
+
+        let (left, top) = (x.floor(), y.floor());
+        let (weight_x, weight_y) = (x - left, y - top);
+        let (left, top) = (left as i64, top as i64);
+
+        let upper = self
+            .get_pixel(left, top)
+            .times(1.0 - weight_x)
+            .plus(&self.get_pixel(left + 1, top).times(weight_x));
+        let lower = self
+            .get_pixel(left, top + 1)
+            .times(1.0 - weight_x)
+            .plus(&self.get_pixel(left + 1, top + 1).times(weight_x));
+
+        upper.times(1.0 - weight_y).plus(&lower.times(weight_y))
+    }
+}
+
+fn decode_srgb(value: u8) -> f32 {
+    let value = value as f32 / 255.0;
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
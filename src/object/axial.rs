@@ -0,0 +1,138 @@
+use std::f32::consts::PI;
+
+use crate::primitive::{contact::RayContact, ray::Ray, transform::Transform, vector::Vector};
+
+// Sine of the angle below which a ray counts as parallel to a flat surface.
+const PARALLEL_TOLERANCE: f32 = 1e-6;
+
+/*
+ * Helpers for the shapes that are built around the y axis of their own space (disks, cylinders,
+ * cones and tori) and placed in the scene by a rigid transform. The transform keeps distances, so
+ * multipliers along the ray are the same in both spaces.
+ */
+
+pub struct LocalHit {
+    pub multiplier: f32,
+    pub position: Vector,
+    pub normal: Vector,
+    pub uv: [f32; 2],
+    pub tangents: [Vector; 2],
+}
+
+impl LocalHit {
+    // The nearer of two hits in front of the ray's origin.
+    pub fn closest(hit: Option<LocalHit>, other: Option<LocalHit>) -> Option<LocalHit> {
+        match (hit, other) {
+            (Some(hit), Some(other)) => Some(if other.multiplier < hit.multiplier {
+                other
+            } else {
+                hit
+            }),
+            (hit, None) => hit,
+            (None, other) => other,
+        }
+    }
+
+    pub fn into_contact<'a>(self, placement: &Transform, ray: &'a Ray) -> RayContact<'a> {
+        RayContact::new(
+            placement.apply_to_point(&self.position),
+            placement.apply_to_direction(&self.normal),
+            ray,
+        )
+        .with_uv(self.uv)
+        .with_tangents(
+            placement.apply_to_direction(&self.tangents[0]),
+            placement.apply_to_direction(&self.tangents[1]),
+        )
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Disk facing up at height `y`, hit from either side. Its coordinates map the square around it.
+pub fn intersect_disk(ray: &Ray, y: f32, radius: f32, normal_y: f32) -> Option<LocalHit> {
+    if is_parallel_to_plane(&ray.direction) {
+        return None;
+    }
+
+    let multiplier = (y - ray.origin.y) / ray.direction.y;
+    if multiplier <= 0.0 {
+        return None;
+    }
+
+    let position = ray.origin.plus(&ray.direction.times(multiplier));
+    if position.x * position.x + position.z * position.z > radius * radius {
+        return None;
+    }
+
+    Some(LocalHit {
+        multiplier,
+        position: Vector::new(position.x, y, position.z),
+        normal: Vector::new(0.0, normal_y, 0.0),
+        uv: [
+            0.5 + position.x / (2.0 * radius),
+            0.5 + position.z / (2.0 * radius),
+        ],
+        tangents: [Vector::x(), Vector::z()],
+    })
+}
+
+/*
+ * Whether a direction runs along the planes perpendicular to the y axis. Rays that do so in a
+ * placed shape's space come out of the transform slightly tilted, which would make them hit a flat
+ * surface they lie in.
+ */
+pub fn is_parallel_to_plane(direction: &Vector) -> bool {
+    direction.y.abs() <= PARALLEL_TOLERANCE * direction.len()
+}
+
+// Roots of `a·t² + b·t + c`, the smaller one first.
+pub fn solve_quadratic(a: f32, b: f32, c: f32) -> Option<[f32; 2]> {
+    if a == 0.0 {
+        if b == 0.0 {
+            return None;
+        }
+        let root = -c / b;
+        return Some([root, root]);
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    // Avoids subtracting nearly equal numbers, which loses the precision of the smaller root.
+    let q = -0.5 * (b + discriminant.sqrt().copysign(b));
+    let roots = if q == 0.0 { [0.0, 0.0] } else { [q / a, c / q] };
+
+    Some(if roots[0] <= roots[1] {
+        roots
+    } else {
+        [roots[1], roots[0]]
+    })
+}
+
+// Angle around the y axis as a coordinate in [0, 1).
+pub fn get_azimuth(position: &Vector) -> f32 {
+    (position.z.atan2(position.x) / (2.0 * PI)).rem_euclid(1.0)
+}
+
+// Direction in which the azimuth grows, zero on the axis.
+pub fn get_azimuth_tangent(position: &Vector) -> Vector {
+    Vector::new(-position.z, 0.0, position.x)
+}
+
+// Picks one of several surface parts proportionally to its area and returns it with the random
+// number rescaled to [0, 1) within the part, so it can still place the point on it.
+pub fn pick_part(areas: &[f32], random: f32) -> (usize, f32) {
+    let total: f32 = areas.iter().sum();
+    let mut remaining = random * total;
+    for (index, area) in areas.iter().enumerate() {
+        if remaining < *area {
+            return (index, (remaining / area).min(1.0));
+        }
+        remaining -= area;
+    }
+
+    (areas.len() - 1, 1.0)
+}
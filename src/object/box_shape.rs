@@ -0,0 +1,311 @@
+use crate::primitive::{bounding_box::BoundingBox, contact::RayContact, ray::Ray, vector::Vector};
+
+use super::Shape;
+
+/*
+ * Box between two corners, tested against the ray one pair of parallel faces (slab) at a time.
+ * The ray is inside the box where it's inside all three slabs at once, so it enters at the
+ * largest of the near distances and leaves at the smallest of the far ones.
+ */
+pub struct BoxShape {
+    center: Vector,
+    half_size: [f32; 3],
+    // Width, height and length directions, the world axes unless the box is rotated.
+    axes: [Vector; 3],
+    // Inverted boxes face inwards, as walls of a room that's seen from inside.
+    normal_multiplier: f32,
+}
+
+impl BoxShape {
+    pub fn new(center: Vector, width: f32, length: f32, height: f32) -> Self {
+        Self {
+            center,
+            half_size: [width / 2.0, height / 2.0, length / 2.0],
+            axes: [Vector::x(), Vector::y(), Vector::z()],
+            normal_multiplier: 1.0,
+        }
+    }
+
+    pub fn new_oriented(
+        center: Vector,
+        width: f32,
+        length: f32,
+        height: f32,
+        rotation_vector: Vector,
+        rotation_angle: f32,
+    ) -> Self {
+        let mut axes = [Vector::x(), Vector::y(), Vector::z()];
+        for axis in &mut axes {
+            axis.rotate_around_vector(&rotation_vector, rotation_angle);
+            axis.normalize();
+        }
+
+        Self {
+            axes,
+            ..Self::new(center, width, length, height)
+        }
+    }
+
+    pub fn inverted(self) -> Self {
+        Self {
+            normal_multiplier: -self.normal_multiplier,
+            ..self
+        }
+    }
+
+    fn to_world(&self, local: [f32; 3]) -> Vector {
+        self.axes[0]
+            .times(local[0])
+            .plus(&self.axes[1].times(local[1]))
+            .plus(&self.axes[2].times(local[2]))
+    }
+}
+
+impl Shape for BoxShape {
+    fn get_contact<'a>(&self, ray: &'a Ray) -> Option<RayContact<'a>> {
+        let from_center = ray.origin.minus(&self.center);
+
+        let mut entry = (f32::NEG_INFINITY, 0);
+        let mut exit = (f32::INFINITY, 0);
+        for axis in 0..3 {
+            let origin = from_center.dot(&self.axes[axis]);
+            let direction = ray.direction.dot(&self.axes[axis]);
+            let half_size = self.half_size[axis];
+
+            // Rays parallel to a slab never cross its faces, they're either inside it or miss.
+            if direction == 0.0 {
+                if origin.abs() > half_size {
+                    return None;
+                }
+                continue;
+            }
+
+            let near = (-half_size.copysign(direction) - origin) / direction;
+            let far = (half_size.copysign(direction) - origin) / direction;
+            if near > entry.0 {
+                entry = (near, axis);
+            }
+            if far < exit.0 {
+                exit = (far, axis);
+            }
+        }
+
+        if entry.0 > exit.0 || exit.0 <= 0.0 {
+            return None;
+        }
+
+        // Rays starting inside the box hit it where they leave.
+        let (multiplier, axis, side) = if entry.0 > 0.0 {
+            (entry.0, entry.1, -1.0)
+        } else {
+            (exit.0, exit.1, 1.0)
+        };
+
+        let position = ray.origin.plus(&ray.direction.times(multiplier));
+        let mut local = [0.0; 3];
+        for (index, value) in local.iter_mut().enumerate() {
+            *value = position
+                .minus(&self.center)
+                .dot(&self.axes[index])
+                .clamp(-self.half_size[index], self.half_size[index]);
+        }
+
+        // The hit face is the one the ray crosses, snapping to it keeps rounding off the surface.
+        let face_sign = side * ray.direction.dot(&self.axes[axis]).signum();
+        local[axis] = face_sign * self.half_size[axis];
+
+        // Each face maps the square of its own two axes.
+        let (u_axis, v_axis) = ((axis + 1) % 3, (axis + 2) % 3);
+        let uv = [
+            0.5 + local[u_axis] / (2.0 * self.half_size[u_axis]),
+            0.5 + local[v_axis] / (2.0 * self.half_size[v_axis]),
+        ];
+
+        Some(
+            RayContact::new(
+                self.center.plus(&self.to_world(local)),
+                self.axes[axis].times(face_sign * self.normal_multiplier),
+                ray,
+            )
+            .with_uv(uv)
+            .with_tangents(self.axes[u_axis], self.axes[v_axis]),
+        )
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        let mut corners = Vec::with_capacity(8);
+        for corner in 0..8 {
+            let local = [
+                if corner & 1 == 0 { -1.0 } else { 1.0 } * self.half_size[0],
+                if corner & 2 == 0 { -1.0 } else { 1.0 } * self.half_size[1],
+                if corner & 4 == 0 { -1.0 } else { 1.0 } * self.half_size[2],
+            ];
+            corners.push(self.center.plus(&self.to_world(local)));
+        }
+
+        BoundingBox::around_points(&corners)
+    }
+
+    fn get_surface_area(&self) -> Option<f32> {
+        let [x, y, z] = self.half_size;
+        Some(8.0 * (x * y + y * z + z * x))
+    }
+
+    fn sample_surface(&self, random: [f32; 2]) -> Option<Vector> {
+        // Picks a face proportionally to its area and reuses the rest of the first random number
+        // to place the point on it.
+        let mut remaining = random[0] * self.get_surface_area()?;
+        for face in 0..6 {
+            let axis = face / 2;
+            let (u_axis, v_axis) = ((axis + 1) % 3, (axis + 2) % 3);
+            let area = 4.0 * self.half_size[u_axis] * self.half_size[v_axis];
+            if remaining < area || face == 5 {
+                let u = if area > 0.0 {
+                    (remaining / area).min(1.0)
+                } else {
+                    0.5
+                };
+
+                let mut local = [0.0; 3];
+                local[axis] = if face % 2 == 0 { 1.0 } else { -1.0 } * self.half_size[axis];
+                local[u_axis] = (2.0 * u - 1.0) * self.half_size[u_axis];
+                local[v_axis] = (2.0 * random[1] - 1.0) * self.half_size[v_axis];
+
+                return Some(self.center.plus(&self.to_world(local)));
+            }
+            remaining -= area;
+        }
+
+        None
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use super::*;
+    use crate::object::testing::{assert_close, assert_vector_close, ray};
+
+    // Half sizes of 1, 3 and 2 along x, y and z, different so a wrong axis shows.
+    fn unit_box() -> BoxShape {
+        BoxShape::new(Vector::new(0.0, 0.0, 0.0), 2.0, 4.0, 6.0)
+    }
+
+    fn oriented_box() -> (BoxShape, [Vector; 3]) {
+        let axis = Vector::new(1.0, 1.0, 0.0).normalized();
+        let shape =
+            BoxShape::new_oriented(Vector::new(1.0, -2.0, 0.5), 2.0, 4.0, 6.0, axis, FRAC_PI_4);
+        let mut axes = [Vector::x(), Vector::y(), Vector::z()];
+        for local_axis in &mut axes {
+            local_axis.rotate_around_vector(&axis, FRAC_PI_4);
+        }
+
+        (shape, axes)
+    }
+
+    // Rays along both directions of each axis, from 10 away from the center.
+    fn check_all_sides(shape: &BoxShape, center: &Vector, axes: &[Vector; 3]) {
+        let half_size = [1.0, 3.0, 2.0];
+        for (axis, direction) in axes.iter().enumerate() {
+            for sign in &[1.0, -1.0] {
+                let direction = direction.times(*sign);
+                let incoming = ray(center.minus(&direction.times(10.0)), direction);
+                let contact = shape
+                    .get_contact(&incoming)
+                    .expect("the ray should hit the box");
+
+                assert_close(contact.get_distance_from_origin(), 10.0 - half_size[axis]);
+                assert_vector_close(&contact.get_normal(), &direction.times(-1.0));
+                assert!(!contact.is_from_inside());
+            }
+        }
+    }
+
+    #[test]
+    fn hits_axis_aligned_box_from_all_sides() {
+        let axes = [Vector::x(), Vector::y(), Vector::z()];
+        check_all_sides(&unit_box(), &Vector::new(0.0, 0.0, 0.0), &axes);
+    }
+
+    #[test]
+    fn hits_oriented_box_from_all_sides() {
+        let (shape, axes) = oriented_box();
+        check_all_sides(&shape, &Vector::new(1.0, -2.0, 0.5), &axes);
+    }
+
+    #[test]
+    fn hits_box_where_rays_from_inside_leave() {
+        let shape = unit_box();
+        let cases = [
+            (Vector::x(), 1.0),
+            (Vector::x().times(-1.0), 1.0),
+            (Vector::y(), 3.0),
+            (Vector::y().times(-1.0), 3.0),
+            (Vector::z(), 2.0),
+            (Vector::z().times(-1.0), 2.0),
+        ];
+        for (direction, distance) in &cases {
+            let incoming = ray(Vector::new(0.0, 0.0, 0.0), *direction);
+            let contact = shape
+                .get_contact(&incoming)
+                .expect("the ray should leave the box");
+
+            assert_close(contact.get_distance_from_origin(), *distance);
+            assert!(contact.is_from_inside());
+            assert_vector_close(&contact.get_normal(), direction);
+            assert_vector_close(&contact.get_facing_normal(), &direction.times(-1.0));
+        }
+    }
+
+    #[test]
+    fn hits_oriented_box_where_rays_from_inside_leave() {
+        let (shape, axes) = oriented_box();
+        let incoming = ray(Vector::new(1.0, -2.0, 0.5), axes[1]);
+        let contact = shape
+            .get_contact(&incoming)
+            .expect("the ray should leave the box");
+
+        assert_close(contact.get_distance_from_origin(), 3.0);
+        assert!(contact.is_from_inside());
+        assert_vector_close(&contact.get_facing_normal(), &axes[1].times(-1.0));
+    }
+
+    // The six-plane cube returned whichever face it tested first, back faces included.
+    #[test]
+    fn returns_nearest_face_rather_than_back_face() {
+        let shape = unit_box();
+        let incoming = ray(Vector::new(-5.0, -5.0, -5.0), Vector::new(1.0, 1.0, 1.0));
+        let contact = shape
+            .get_contact(&incoming)
+            .expect("the ray should hit the box");
+
+        // Inside the x slab from 4 to 6, the y slab from 2 to 8 and the z slab from 3 to 7.
+        assert_close(contact.get_distance_from_origin(), 4.0 * 3.0_f32.sqrt());
+        assert_vector_close(&contact.get_position(), &Vector::new(-1.0, -1.0, -1.0));
+        assert_vector_close(&contact.get_normal(), &Vector::x().times(-1.0));
+
+        for face_order_ray in &[
+            ray(Vector::new(0.2, 0.5, 10.0), Vector::z().times(-1.0)),
+            ray(Vector::new(0.2, -10.0, 0.5), Vector::y()),
+        ] {
+            let contact = shape
+                .get_contact(face_order_ray)
+                .expect("the ray should hit the box");
+            assert!(contact.get_normal().dot(&face_order_ray.direction) < 0.0);
+        }
+    }
+
+    #[test]
+    fn misses_box_beside_or_behind_the_ray() {
+        let shape = unit_box();
+        assert!(shape
+            .get_contact(&ray(Vector::new(1.5, 0.0, -10.0), Vector::z()))
+            .is_none());
+        assert!(shape
+            .get_contact(&ray(Vector::new(0.0, 0.0, 10.0), Vector::z()))
+            .is_none());
+    }
+}
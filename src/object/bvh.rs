@@ -0,0 +1,225 @@
+use crate::primitive::{bounding_box::BoundingBox, ray::Ray, vector::Vector};
+
+const BIN_COUNT: usize = 12;
+const MAX_LEAF_SIZE: usize = 4;
+const TRAVERSAL_COST: f32 = 1.0;
+const INTERSECTION_COST: f32 = 1.0;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+struct BvhNode {
+    bounding_box: BoundingBox,
+
+    // Leaves reference `count` items starting at `first`, interior nodes store the index of
+    // their second child in `first` and have their first child right after them.
+    first: usize,
+    count: usize,
+    axis: usize,
+}
+
+#[derive(Clone, Copy)]
+struct Bin {
+    bounding_box: BoundingBox,
+    count: usize,
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    item_ids: Vec<usize>,
+}
+
+impl Bvh {
+    pub fn empty() -> Self {
+        Self {
+            nodes: Vec::new(),
+            item_ids: Vec::new(),
+        }
+    }
+
+    pub fn build(bounding_boxes: &[BoundingBox]) -> Self {
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(2 * bounding_boxes.len()),
+            item_ids: (0..bounding_boxes.len()).collect(),
+        };
+
+        if !bounding_boxes.is_empty() {
+            let centers: Vec<Vector> = bounding_boxes
+                .iter()
+                .map(|bounding_box| bounding_box.get_center())
+                .collect();
+            bvh.build_node(bounding_boxes, &centers, 0, bounding_boxes.len());
+        }
+
+        bvh
+    }
+
+    pub fn get_bounding_box(&self) -> BoundingBox {
+        match self.nodes.first() {
+            Some(root) => root.bounding_box,
+            None => BoundingBox::empty(),
+        }
+    }
+
+    /*
+     * Visits the items whose bounding box the ray enters before `max_distance`, nearest nodes
+     * first. `intersect` returns the distance of a hit on the item, which then becomes the new
+     * limit for the rest of the traversal.
+     */
+    pub fn traverse<F>(&self, ray: &Ray, max_distance: f32, mut intersect: F)
+    where
+        F: FnMut(usize) -> Option<f32>,
+    {
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        let direction_length = ray.direction.len();
+        let inverse_direction = Vector::new(
+            1.0 / ray.direction.x,
+            1.0 / ray.direction.y,
+            1.0 / ray.direction.z,
+        );
+        let direction_is_negative = [
+            ray.direction.x < 0.0,
+            ray.direction.y < 0.0,
+            ray.direction.z < 0.0,
+        ];
+
+        let mut max_multiplier = max_distance / direction_length;
+        let mut stack = Vec::with_capacity(64);
+        stack.push(0);
+
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if node
+                .bounding_box
+                .get_entry_multiplier(ray, &inverse_direction, max_multiplier)
+                .is_none()
+            {
+                continue;
+            }
+
+            if node.count > 0 {
+                for item_id in &self.item_ids[node.first..node.first + node.count] {
+                    if let Some(distance) = intersect(*item_id) {
+                        max_multiplier = max_multiplier.min(distance / direction_length);
+                    }
+                }
+            } else if direction_is_negative[node.axis] {
+                stack.push(node_index + 1);
+                stack.push(node.first);
+            } else {
+                stack.push(node.first);
+                stack.push(node_index + 1);
+            }
+        }
+    }
+
+    fn build_node(
+        &mut self,
+        bounding_boxes: &[BoundingBox],
+        centers: &[Vector],
+        start: usize,
+        end: usize,
+    ) -> usize {
+        let node_index = self.nodes.len();
+
+        let mut bounding_box = BoundingBox::empty();
+        let mut center_bounds = BoundingBox::empty();
+        for item_id in &self.item_ids[start..end] {
+            bounding_box = bounding_box.union(&bounding_boxes[*item_id]);
+            center_bounds = center_bounds.including(&centers[*item_id]);
+        }
+
+        self.nodes.push(BvhNode {
+            bounding_box,
+            first: start,
+            count: end - start,
+            axis: 0,
+        });
+
+        let count = end - start;
+        if count <= 1 {
+            return node_index;
+        }
+
+        let axis = center_bounds.get_largest_axis();
+        let axis_min = center_bounds.min.get_axis(axis);
+        let axis_extent = center_bounds.max.get_axis(axis) - axis_min;
+        if axis_extent <= 0.0 {
+            return node_index;
+        }
+
+        let get_bin_index = |item_id: usize| -> usize {
+            let offset = (centers[item_id].get_axis(axis) - axis_min) / axis_extent;
+            ((offset * BIN_COUNT as f32) as usize).min(BIN_COUNT - 1)
+        };
+
+        let mut bins = [Bin {
+            bounding_box: BoundingBox::empty(),
+            count: 0,
+        }; BIN_COUNT];
+        for item_id in &self.item_ids[start..end] {
+            let bin = &mut bins[get_bin_index(*item_id)];
+            bin.bounding_box = bin.bounding_box.union(&bounding_boxes[*item_id]);
+            bin.count += 1;
+        }
+
+        let mut best_split = 0;
+        let mut best_cost = f32::INFINITY;
+        for split in 1..BIN_COUNT {
+            let (left, right) = bins.split_at(split);
+            let cost = get_bins_cost(left) + get_bins_cost(right);
+            if cost < best_cost {
+                best_cost = cost;
+                best_split = split;
+            }
+        }
+
+        let parent_area = bounding_box.get_surface_area();
+        let split_cost = TRAVERSAL_COST + INTERSECTION_COST * best_cost / parent_area;
+        let leaf_cost = INTERSECTION_COST * count as f32;
+        if count <= MAX_LEAF_SIZE && (leaf_cost <= split_cost || !split_cost.is_finite()) {
+            return node_index;
+        }
+
+        let mut middle = start;
+        for i in start..end {
+            if get_bin_index(self.item_ids[i]) < best_split {
+                self.item_ids.swap(i, middle);
+                middle += 1;
+            }
+        }
+
+        if middle == start || middle == end {
+            middle = start + count / 2;
+        }
+
+        self.build_node(bounding_boxes, centers, start, middle);
+        let second_child = self.build_node(bounding_boxes, centers, middle, end);
+
+        let node = &mut self.nodes[node_index];
+        node.first = second_child;
+        node.count = 0;
+        node.axis = axis;
+
+        node_index
+    }
+}
+
+fn get_bins_cost(bins: &[Bin]) -> f32 {
+    let mut bounding_box = BoundingBox::empty();
+    let mut count = 0;
+    for bin in bins {
+        bounding_box = bounding_box.union(&bin.bounding_box);
+        count += bin.count;
+    }
+
+    if count == 0 {
+        0.0
+    } else {
+        count as f32 * bounding_box.get_surface_area()
+    }
+}
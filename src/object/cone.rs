@@ -0,0 +1,267 @@
+use std::f32::consts::PI;
+
+use crate::primitive::{
+    bounding_box::BoundingBox, contact::RayContact, ray::Ray, transform::Transform, vector::Vector,
+};
+
+use super::{
+    axial::{
+        get_azimuth, get_azimuth_tangent, intersect_disk, pick_part, solve_quadratic, LocalHit,
+    },
+    Shape,
+};
+
+// Cone with its base around `center` and its tip `height` along `axis`.
+pub struct ConeShape {
+    placement: Transform,
+    radius: f32,
+    height: f32,
+    is_capped: bool,
+}
+
+impl ConeShape {
+    pub fn new(center: Vector, axis: Vector, radius: f32, height: f32, is_capped: bool) -> Self {
+        Self {
+            placement: Transform::placing(&center, &axis),
+            radius,
+            height,
+            is_capped,
+        }
+    }
+
+    fn get_side_area(&self) -> f32 {
+        PI * self.radius * self.radius.hypot(self.height)
+    }
+
+    fn intersect_side(&self, ray: &Ray) -> Option<LocalHit> {
+        let (origin, direction) = (&ray.origin, &ray.direction);
+
+        // The radius shrinks by `slope` for every unit towards the tip.
+        let slope = self.radius / self.height;
+        let slope_sqr = slope * slope;
+        let to_tip = self.height - origin.y;
+
+        let a = direction.x * direction.x + direction.z * direction.z
+            - slope_sqr * direction.y * direction.y;
+        let b = 2.0
+            * (origin.x * direction.x + origin.z * direction.z + slope_sqr * to_tip * direction.y);
+        let c = origin.x * origin.x + origin.z * origin.z - slope_sqr * to_tip * to_tip;
+
+        // The equation describes a double cone, hits on the mirrored half past the tip are skipped.
+        let roots = solve_quadratic(a, b, c)?;
+        roots.iter().find_map(|&multiplier| {
+            if multiplier <= 0.0 {
+                return None;
+            }
+
+            let position = origin.plus(&direction.times(multiplier));
+            if position.y < 0.0 || position.y > self.height {
+                return None;
+            }
+
+            let radial = position.x.hypot(position.z);
+            let (normal, up_slope) = if radial > 0.0 {
+                (
+                    Vector::new(position.x / radial, slope, position.z / radial),
+                    Vector::new(
+                        -position.x * slope / radial,
+                        1.0,
+                        -position.z * slope / radial,
+                    ),
+                )
+            } else {
+                (Vector::y(), Vector::y())
+            };
+
+            Some(LocalHit {
+                multiplier,
+                position,
+                normal,
+                uv: [get_azimuth(&position), position.y / self.height],
+                tangents: [get_azimuth_tangent(&position), up_slope],
+            })
+        })
+    }
+}
+
+impl Shape for ConeShape {
+    fn get_contact<'a>(&self, ray: &'a Ray) -> Option<RayContact<'a>> {
+        let local_ray = self.placement.apply_inverse_to_ray(ray);
+
+        let mut hit = self.intersect_side(&local_ray);
+        if self.is_capped {
+            hit = LocalHit::closest(hit, intersect_disk(&local_ray, 0.0, self.radius, -1.0));
+        }
+
+        Some(hit?.into_contact(&self.placement, ray))
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        self.placement.apply_to_bounding_box(&BoundingBox::new(
+            Vector::new(-self.radius, 0.0, -self.radius),
+            Vector::new(self.radius, self.height, self.radius),
+        ))
+    }
+
+    fn get_surface_area(&self) -> Option<f32> {
+        let base = if self.is_capped {
+            PI * self.radius * self.radius
+        } else {
+            0.0
+        };
+
+        Some(self.get_side_area() + base)
+    }
+
+    fn sample_surface(&self, random: [f32; 2]) -> Option<Vector> {
+        let base_area = if self.is_capped {
+            PI * self.radius * self.radius
+        } else {
+            0.0
+        };
+
+        let (part, u) = pick_part(&[self.get_side_area(), base_area], random[0]);
+        let phi = 2.0 * PI * random[1];
+        // The side's area grows linearly with the distance from the tip, as does the base's with
+        // the distance from its center.
+        let fraction = u.sqrt();
+        let local = if part == 0 {
+            Vector::new(
+                self.radius * fraction * phi.cos(),
+                self.height * (1.0 - fraction),
+                self.radius * fraction * phi.sin(),
+            )
+        } else {
+            Vector::new(
+                self.radius * fraction * phi.cos(),
+                0.0,
+                self.radius * fraction * phi.sin(),
+            )
+        };
+
+        Some(self.placement.apply_to_point(&local))
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::testing::{
+        assert_close, assert_finite, assert_sampled_share, assert_uv_in_range, assert_vector_close,
+        ray, sample_grid,
+    };
+
+    // Base of radius 1 at the origin, tip at y = 2.
+    fn cone() -> ConeShape {
+        ConeShape::new(Vector::new(0.0, 0.0, 0.0), Vector::y(), 1.0, 2.0, true)
+    }
+
+    #[test]
+    fn hits_cone_side_and_base_from_outside() {
+        let shape = cone();
+
+        // Halfway up the radius is 0.5.
+        let at_side = ray(Vector::new(-5.0, 1.0, 0.0), Vector::x());
+        let contact = shape.get_contact(&at_side).expect("the ray should hit");
+        assert_close(contact.get_distance_from_origin(), 4.5);
+        assert_vector_close(
+            &contact.get_normal(),
+            &Vector::new(-1.0, 0.5, 0.0).normalized(),
+        );
+        assert!(!contact.is_from_inside());
+
+        let at_base = ray(Vector::new(0.2, -3.0, 0.3), Vector::y());
+        let contact = shape.get_contact(&at_base).expect("the ray should hit");
+        assert_close(contact.get_distance_from_origin(), 3.0);
+        assert_vector_close(&contact.get_normal(), &Vector::y().times(-1.0));
+    }
+
+    #[test]
+    fn hits_cone_where_rays_from_inside_leave() {
+        let shape = cone();
+
+        // A quarter of the way up the radius is 0.75.
+        let through_side = ray(Vector::new(0.0, 0.5, 0.0), Vector::x());
+        let contact = shape
+            .get_contact(&through_side)
+            .expect("the ray should leave");
+        assert_close(contact.get_distance_from_origin(), 0.75);
+        assert!(contact.is_from_inside());
+        assert_vector_close(
+            &contact.get_facing_normal(),
+            &Vector::new(-1.0, -0.5, 0.0).normalized(),
+        );
+
+        let through_base = ray(Vector::new(0.0, 0.5, 0.0), Vector::y().times(-1.0));
+        let contact = shape
+            .get_contact(&through_base)
+            .expect("the ray should leave");
+        assert_close(contact.get_distance_from_origin(), 0.5);
+        assert!(contact.is_from_inside());
+        assert_vector_close(&contact.get_facing_normal(), &Vector::y());
+    }
+
+    #[test]
+    fn skips_mirrored_cone_past_the_tip() {
+        let shape = cone();
+        let above_tip = ray(Vector::new(-5.0, 3.0, 0.0), Vector::x());
+        assert!(shape.get_contact(&above_tip).is_none());
+    }
+
+    #[test]
+    fn grazes_cone_cleanly() {
+        let shape = cone();
+
+        let tangent = ray(Vector::new(-5.0, 1.0, 0.5), Vector::x());
+        if let Some(contact) = shape.get_contact(&tangent) {
+            assert_finite(&contact);
+            assert_close(contact.get_distance_from_origin(), 5.0);
+        }
+
+        // Down the axis onto the tip, where the side has no azimuth.
+        let onto_tip = ray(Vector::new(0.0, 5.0, 0.0), Vector::y().times(-1.0));
+        let contact = shape.get_contact(&onto_tip).expect("the ray should hit");
+        assert_finite(&contact);
+        assert_close(contact.get_distance_from_origin(), 3.0);
+
+        // Along the slope of the side.
+        let along_side = ray(Vector::new(-1.5, -1.0, 0.0), Vector::new(0.5, 1.0, 0.0));
+        if let Some(contact) = shape.get_contact(&along_side) {
+            assert_finite(&contact);
+        }
+    }
+
+    #[test]
+    fn maps_cone_into_unit_square() {
+        let shape = cone();
+        for i in 0..16 {
+            let angle = i as f32 * PI / 8.0;
+            let direction = Vector::new(angle.cos(), 0.2, angle.sin());
+            let incoming = ray(direction.times(-3.0), direction);
+            let contact = shape.get_contact(&incoming).expect("the ray should hit");
+            assert_uv_in_range(&contact);
+        }
+    }
+
+    #[test]
+    fn samples_cone_uniformly() {
+        let shape = cone();
+        let points = sample_grid(&shape);
+        for point in &points {
+            let radial = point.x.hypot(point.z);
+            let on_side =
+                (radial - 0.5 * (2.0 - point.y)).abs() < 1e-4 && (0.0..=2.0).contains(&point.y);
+            let on_base = point.y.abs() < 1e-4 && radial <= 1.0 + 1e-4;
+            assert!(on_side || on_base, "{:?} isn't on the surface", point);
+        }
+
+        // The half of the side nearer the tip covers a quarter of the side's area.
+        let side_area = PI * 5.0_f32.sqrt();
+        let area = shape.get_surface_area().unwrap();
+        assert_close(area, side_area + PI);
+        assert_sampled_share(&points, |point| point.y > 1.0, 0.25 * side_area / area);
+        assert_sampled_share(&points, |point| point.y.abs() < 1e-4, PI / area);
+    }
+}
@@ -0,0 +1,247 @@
+use std::f32::consts::PI;
+
+use crate::primitive::{
+    bounding_box::BoundingBox, contact::RayContact, ray::Ray, transform::Transform, vector::Vector,
+};
+
+use super::{
+    axial::{
+        get_azimuth, get_azimuth_tangent, intersect_disk, pick_part, solve_quadratic, LocalHit,
+    },
+    Shape,
+};
+
+// Cylinder around `axis` through `center`, reaching half its height to either side.
+pub struct CylinderShape {
+    placement: Transform,
+    radius: f32,
+    half_height: f32,
+    is_capped: bool,
+}
+
+impl CylinderShape {
+    pub fn new(center: Vector, axis: Vector, radius: f32, height: f32, is_capped: bool) -> Self {
+        Self {
+            placement: Transform::placing(&center, &axis),
+            radius,
+            half_height: height / 2.0,
+            is_capped,
+        }
+    }
+
+    fn intersect_side(&self, ray: &Ray) -> Option<LocalHit> {
+        let (origin, direction) = (&ray.origin, &ray.direction);
+        let a = direction.x * direction.x + direction.z * direction.z;
+        // Rays along the axis never cross the side.
+        if a == 0.0 {
+            return None;
+        }
+
+        let b = 2.0 * (origin.x * direction.x + origin.z * direction.z);
+        let c = origin.x * origin.x + origin.z * origin.z - self.radius * self.radius;
+
+        // The far root is the hit from inside the tube, or the inner wall of an open one.
+        let roots = solve_quadratic(a, b, c)?;
+        roots.iter().find_map(|&multiplier| {
+            if multiplier <= 0.0 {
+                return None;
+            }
+
+            let position = origin.plus(&direction.times(multiplier));
+            if position.y.abs() > self.half_height {
+                return None;
+            }
+
+            let normal = Vector::new(position.x, 0.0, position.z).normalized();
+            Some(LocalHit {
+                multiplier,
+                position: normal
+                    .times(self.radius)
+                    .plus(&Vector::new(0.0, position.y, 0.0)),
+                normal,
+                uv: [
+                    get_azimuth(&position),
+                    0.5 + position.y / (2.0 * self.half_height),
+                ],
+                tangents: [get_azimuth_tangent(&position), Vector::y()],
+            })
+        })
+    }
+}
+
+impl Shape for CylinderShape {
+    fn get_contact<'a>(&self, ray: &'a Ray) -> Option<RayContact<'a>> {
+        let local_ray = self.placement.apply_inverse_to_ray(ray);
+
+        let mut hit = self.intersect_side(&local_ray);
+        if self.is_capped {
+            for side in [-1.0, 1.0] {
+                let cap = intersect_disk(&local_ray, side * self.half_height, self.radius, side);
+                hit = LocalHit::closest(hit, cap);
+            }
+        }
+
+        Some(hit?.into_contact(&self.placement, ray))
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        let extent = Vector::new(self.radius, self.half_height, self.radius);
+        self.placement
+            .apply_to_bounding_box(&BoundingBox::new(extent.times(-1.0), extent))
+    }
+
+    fn get_surface_area(&self) -> Option<f32> {
+        let side = 4.0 * PI * self.radius * self.half_height;
+        let caps = if self.is_capped {
+            2.0 * PI * self.radius * self.radius
+        } else {
+            0.0
+        };
+
+        Some(side + caps)
+    }
+
+    fn sample_surface(&self, random: [f32; 2]) -> Option<Vector> {
+        let side_area = 4.0 * PI * self.radius * self.half_height;
+        let cap_area = if self.is_capped {
+            PI * self.radius * self.radius
+        } else {
+            0.0
+        };
+
+        let (part, u) = pick_part(&[side_area, cap_area, cap_area], random[0]);
+        let phi = 2.0 * PI * random[1];
+        let local = match part {
+            0 => Vector::new(
+                self.radius * phi.cos(),
+                (2.0 * u - 1.0) * self.half_height,
+                self.radius * phi.sin(),
+            ),
+            _ => {
+                let radius = self.radius * u.sqrt();
+                let y = if part == 1 {
+                    -self.half_height
+                } else {
+                    self.half_height
+                };
+                Vector::new(radius * phi.cos(), y, radius * phi.sin())
+            }
+        };
+
+        Some(self.placement.apply_to_point(&local))
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::testing::{
+        assert_close, assert_finite, assert_sampled_share, assert_uv_in_range, assert_vector_close,
+        ray, sample_grid,
+    };
+
+    // Radius 1, from -1 to 1 along y.
+    fn cylinder(is_capped: bool) -> CylinderShape {
+        CylinderShape::new(Vector::new(0.0, 0.0, 0.0), Vector::y(), 1.0, 2.0, is_capped)
+    }
+
+    #[test]
+    fn hits_cylinder_side_and_caps_from_outside() {
+        let shape = cylinder(true);
+
+        let at_side = ray(Vector::new(-5.0, 0.5, 0.0), Vector::x());
+        let contact = shape.get_contact(&at_side).expect("the ray should hit");
+        assert_close(contact.get_distance_from_origin(), 4.0);
+        assert_vector_close(&contact.get_normal(), &Vector::x().times(-1.0));
+        assert!(!contact.is_from_inside());
+
+        let at_cap = ray(Vector::new(0.2, 5.0, 0.3), Vector::y().times(-1.0));
+        let contact = shape.get_contact(&at_cap).expect("the ray should hit");
+        assert_close(contact.get_distance_from_origin(), 4.0);
+        assert_vector_close(&contact.get_normal(), &Vector::y());
+    }
+
+    #[test]
+    fn hits_cylinder_where_rays_from_inside_leave() {
+        let shape = cylinder(true);
+
+        let through_side = ray(Vector::new(0.0, 0.0, 0.0), Vector::z());
+        let contact = shape
+            .get_contact(&through_side)
+            .expect("the ray should leave");
+        assert_close(contact.get_distance_from_origin(), 1.0);
+        assert!(contact.is_from_inside());
+        assert_vector_close(&contact.get_facing_normal(), &Vector::z().times(-1.0));
+
+        let through_cap = ray(Vector::new(0.0, 0.5, 0.0), Vector::y());
+        let contact = shape
+            .get_contact(&through_cap)
+            .expect("the ray should leave");
+        assert_close(contact.get_distance_from_origin(), 0.5);
+        assert!(contact.is_from_inside());
+        assert_vector_close(&contact.get_facing_normal(), &Vector::y().times(-1.0));
+    }
+
+    #[test]
+    fn sees_inner_wall_of_open_cylinder() {
+        let shape = cylinder(false);
+        assert!(shape
+            .get_contact(&ray(Vector::new(0.0, 5.0, 0.0), Vector::y().times(-1.0)))
+            .is_none());
+
+        let into_tube = ray(Vector::new(0.0, 2.0, 0.0), Vector::new(1.0, -2.0, 0.0));
+        let contact = shape.get_contact(&into_tube).expect("the ray should hit");
+        assert!(contact.is_from_inside());
+        assert_vector_close(&contact.get_position(), &Vector::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn grazes_cylinder_cleanly() {
+        let shape = cylinder(true);
+
+        let tangent = ray(Vector::new(-5.0, 0.0, 1.0), Vector::x());
+        if let Some(contact) = shape.get_contact(&tangent) {
+            assert_finite(&contact);
+            assert_close(contact.get_distance_from_origin(), 5.0);
+        }
+
+        // Along the side, where the side and the cap meet.
+        let along_side = ray(Vector::new(1.0, 5.0, 0.0), Vector::y().times(-1.0));
+        if let Some(contact) = shape.get_contact(&along_side) {
+            assert_finite(&contact);
+            assert_close(contact.get_distance_from_origin(), 4.0);
+        }
+    }
+
+    #[test]
+    fn maps_cylinder_into_unit_square() {
+        let shape = cylinder(true);
+        for i in 0..16 {
+            let angle = i as f32 * PI / 8.0;
+            let direction = Vector::new(angle.cos(), 0.3, angle.sin());
+            let incoming = ray(direction.times(-3.0), direction);
+            let contact = shape.get_contact(&incoming).expect("the ray should hit");
+            assert_uv_in_range(&contact);
+        }
+    }
+
+    #[test]
+    fn samples_cylinder_uniformly() {
+        let shape = cylinder(true);
+        let points = sample_grid(&shape);
+        for point in &points {
+            let radial = point.x.hypot(point.z);
+            let on_side = (radial - 1.0).abs() < 1e-4 && point.y.abs() <= 1.0 + 1e-4;
+            let on_cap = (point.y.abs() - 1.0).abs() < 1e-4 && radial <= 1.0 + 1e-4;
+            assert!(on_side || on_cap, "{:?} isn't on the surface", point);
+        }
+
+        // The side has twice the area of the two caps.
+        let area = shape.get_surface_area().unwrap();
+        assert_close(area, 6.0 * PI);
+        assert_sampled_share(&points, |point| point.y.abs() < 1.0 - 1e-4, 2.0 / 3.0);
+        assert_sampled_share(&points, |point| point.y > 1.0 - 1e-4, 1.0 / 6.0);
+    }
+}
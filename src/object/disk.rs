@@ -0,0 +1,149 @@
+use std::f32::consts::PI;
+
+use crate::primitive::{
+    bounding_box::BoundingBox, contact::RayContact, ray::Ray, transform::Transform, vector::Vector,
+};
+
+use super::{axial::intersect_disk, Shape};
+
+pub struct DiskShape {
+    placement: Transform,
+    radius: f32,
+}
+
+impl DiskShape {
+    pub fn new(center: Vector, normal: Vector, radius: f32) -> Self {
+        Self {
+            placement: Transform::placing(&center, &normal),
+            radius,
+        }
+    }
+}
+
+impl Shape for DiskShape {
+    fn get_contact<'a>(&self, ray: &'a Ray) -> Option<RayContact<'a>> {
+        let local_ray = self.placement.apply_inverse_to_ray(ray);
+        let hit = intersect_disk(&local_ray, 0.0, self.radius, 1.0)?;
+
+        Some(hit.into_contact(&self.placement, ray))
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        let r = self.radius;
+        let corners = [
+            Vector::new(-r, 0.0, -r),
+            Vector::new(-r, 0.0, r),
+            Vector::new(r, 0.0, -r),
+            Vector::new(r, 0.0, r),
+        ];
+
+        BoundingBox::around_points(&corners.map(|corner| self.placement.apply_to_point(&corner)))
+    }
+
+    fn get_surface_area(&self) -> Option<f32> {
+        Some(PI * self.radius * self.radius)
+    }
+
+    fn sample_surface(&self, random: [f32; 2]) -> Option<Vector> {
+        let radius = self.radius * random[0].sqrt();
+        let phi = 2.0 * PI * random[1];
+
+        Some(self.placement.apply_to_point(&Vector::new(
+            radius * phi.cos(),
+            0.0,
+            radius * phi.sin(),
+        )))
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::testing::{
+        assert_close, assert_finite, assert_sampled_share, assert_uv_in_range, assert_vector_close,
+        ray, sample_grid,
+    };
+
+    fn flat_disk() -> DiskShape {
+        DiskShape::new(Vector::new(0.0, 0.0, 0.0), Vector::y(), 1.0)
+    }
+
+    #[test]
+    fn hits_disk_from_either_side() {
+        let shape = flat_disk();
+
+        let from_above = ray(Vector::new(0.3, 5.0, 0.2), Vector::y().times(-1.0));
+        let contact = shape.get_contact(&from_above).expect("the ray should hit");
+        assert_close(contact.get_distance_from_origin(), 5.0);
+        assert_vector_close(&contact.get_normal(), &Vector::y());
+        assert!(!contact.is_from_inside());
+
+        let from_below = ray(Vector::new(0.3, -2.0, 0.2), Vector::y());
+        let contact = shape.get_contact(&from_below).expect("the ray should hit");
+        assert_close(contact.get_distance_from_origin(), 2.0);
+        assert_vector_close(&contact.get_facing_normal(), &Vector::y().times(-1.0));
+    }
+
+    #[test]
+    fn misses_disk_with_parallel_rays() {
+        let shape = flat_disk();
+        assert!(shape
+            .get_contact(&ray(Vector::new(-5.0, 0.0, 0.0), Vector::x()))
+            .is_none());
+        assert!(shape
+            .get_contact(&ray(Vector::new(-5.0, 0.5, 0.0), Vector::x()))
+            .is_none());
+
+        let normal = Vector::new(1.0, 2.0, -0.5).normalized();
+        let tilted = DiskShape::new(Vector::new(1.0, 2.0, 3.0), normal, 1.0);
+        let [along, _] = normal.get_perpendiculars();
+        let parallel = ray(Vector::new(1.0, 2.0, 3.0).minus(&along.times(5.0)), along);
+        assert!(tilted.get_contact(&parallel).is_none());
+    }
+
+    #[test]
+    fn grazes_disk_cleanly() {
+        let shape = flat_disk();
+
+        // Straight down onto the rim.
+        let onto_rim = ray(Vector::new(1.0, 5.0, 0.0), Vector::y().times(-1.0));
+        if let Some(contact) = shape.get_contact(&onto_rim) {
+            assert_finite(&contact);
+            assert_close(contact.get_distance_from_origin(), 5.0);
+        }
+
+        // Almost along the surface.
+        let skimming = ray(Vector::new(-0.5, 1e-4, 0.0), Vector::new(1.0, -1e-3, 0.0));
+        let contact = shape.get_contact(&skimming).expect("the ray should hit");
+        assert_finite(&contact);
+        assert_vector_close(&contact.get_position(), &Vector::new(-0.4, 0.0, 0.0));
+    }
+
+    #[test]
+    fn maps_disk_into_unit_square() {
+        let shape = flat_disk();
+        for i in 0..=10 {
+            for j in 0..=10 {
+                let origin = Vector::new(i as f32 / 5.0 - 1.0, 1.0, j as f32 / 5.0 - 1.0);
+                if let Some(contact) = shape.get_contact(&ray(origin, Vector::y().times(-1.0))) {
+                    assert_uv_in_range(&contact);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn samples_disk_uniformly() {
+        let shape = flat_disk();
+        let points = sample_grid(&shape);
+        for point in &points {
+            assert!(point.y.abs() < 1e-5 && point.x.hypot(point.z) <= 1.0 + 1e-5);
+        }
+
+        // The inner half of the radius covers a quarter of the area.
+        assert_sampled_share(&points, |point| point.x.hypot(point.z) < 0.5, 0.25);
+        assert_close(shape.get_surface_area().unwrap(), PI);
+    }
+}
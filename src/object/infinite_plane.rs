@@ -0,0 +1,121 @@
+use crate::primitive::{
+    bounding_box::BoundingBox, contact::RayContact, ray::Ray, transform::Transform, vector::Vector,
+};
+
+use super::{axial::is_parallel_to_plane, Shape};
+
+/*
+ * Plane without edges, for grounds that reach the horizon. It's hit from either side, and has an
+ * unbounded box so the scene tests it next to the BVH instead of inside it. Its coordinates are
+ * distances along the plane, so textures repeat across it.
+ */
+pub struct InfinitePlaneShape {
+    placement: Transform,
+}
+
+impl InfinitePlaneShape {
+    pub fn new(point: Vector, normal: Vector) -> Self {
+        Self {
+            placement: Transform::placing(&point, &normal),
+        }
+    }
+}
+
+impl Shape for InfinitePlaneShape {
+    fn get_contact<'a>(&self, ray: &'a Ray) -> Option<RayContact<'a>> {
+        let local_ray = self.placement.apply_inverse_to_ray(ray);
+        if is_parallel_to_plane(&local_ray.direction) {
+            return None;
+        }
+
+        let multiplier = -local_ray.origin.y / local_ray.direction.y;
+        if multiplier <= 0.0 {
+            return None;
+        }
+
+        let position = local_ray
+            .origin
+            .plus(&local_ray.direction.times(multiplier));
+
+        Some(
+            RayContact::new(
+                self.placement
+                    .apply_to_point(&Vector::new(position.x, 0.0, position.z)),
+                self.placement.apply_to_direction(&Vector::y()),
+                ray,
+            )
+            .with_uv([position.x, position.z])
+            .with_tangents(
+                self.placement.apply_to_direction(&Vector::x()),
+                self.placement.apply_to_direction(&Vector::z()),
+            ),
+        )
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        BoundingBox::new(
+            Vector::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+            Vector::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+        )
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::testing::{assert_close, assert_finite, assert_vector_close, ray};
+
+    fn ground() -> InfinitePlaneShape {
+        InfinitePlaneShape::new(Vector::new(0.0, -1.0, 0.0), Vector::y())
+    }
+
+    #[test]
+    fn hits_plane_from_either_side() {
+        let shape = ground();
+
+        let from_above = ray(Vector::new(100.0, 3.0, -40.0), Vector::y().times(-1.0));
+        let contact = shape.get_contact(&from_above).expect("the ray should hit");
+        assert_close(contact.get_distance_from_origin(), 4.0);
+        assert_vector_close(&contact.get_normal(), &Vector::y());
+        assert!(!contact.is_from_inside());
+        assert_eq!(contact.get_uv(), [100.0, -40.0]);
+
+        let from_below = ray(Vector::new(0.0, -3.0, 0.0), Vector::y());
+        let contact = shape.get_contact(&from_below).expect("the ray should hit");
+        assert_close(contact.get_distance_from_origin(), 2.0);
+        assert_vector_close(&contact.get_facing_normal(), &Vector::y().times(-1.0));
+    }
+
+    #[test]
+    fn misses_plane_with_parallel_or_receding_rays() {
+        let shape = ground();
+        assert!(shape
+            .get_contact(&ray(Vector::new(0.0, 0.0, 0.0), Vector::x()))
+            .is_none());
+        assert!(shape
+            .get_contact(&ray(Vector::new(0.0, -1.0, 0.0), Vector::z()))
+            .is_none());
+        assert!(shape
+            .get_contact(&ray(Vector::new(0.0, 0.0, 0.0), Vector::y()))
+            .is_none());
+    }
+
+    #[test]
+    fn grazes_plane_cleanly() {
+        let shape = ground();
+        let skimming = ray(Vector::new(0.0, 0.0, 0.0), Vector::new(1.0, -1e-4, 0.0));
+        let contact = shape.get_contact(&skimming).expect("the ray should hit");
+        assert_finite(&contact);
+        assert_close(contact.get_position().y, -1.0);
+    }
+
+    // Unbounded planes are no lights, they can't be sampled.
+    #[test]
+    fn has_no_area_to_sample() {
+        let shape = ground();
+        assert!(shape.get_surface_area().is_none());
+        assert!(shape.sample_surface([0.5, 0.5]).is_none());
+    }
+}
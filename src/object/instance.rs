@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use crate::primitive::{
+    bounding_box::BoundingBox, contact::RayContact, ray::Ray, transform::Transform, vector::Vector,
+};
+
+use super::{transformed::TransformedShape, Object, Shape, ShapeProperties};
+
+/*
+ * A placed copy of a shape that many objects share. Only the transform and the properties belong
+ * to the instance, so a heavy mesh costs its memory once however often it's placed. The scene's
+ * BVH holds the instances by their transformed bounds, a mesh keeps its own BVH in object space.
+ */
+pub struct Instance {
+    properties: ShapeProperties,
+    shape: TransformedShape<Arc<dyn Shape>>,
+}
+
+impl Instance {
+    pub fn new(properties: ShapeProperties, shape: Arc<dyn Shape>, transform: Transform) -> Self {
+        Self {
+            properties,
+            shape: TransformedShape::new(shape, transform),
+        }
+    }
+}
+
+impl Object for Instance {
+    fn get_contact<'a>(&self, ray: &'a Ray) -> Option<RayContact<'a>> {
+        self.shape.get_contact(ray)
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        self.shape.get_bounding_box()
+    }
+
+    fn get_surface_area(&self) -> Option<f32> {
+        self.shape.get_surface_area()
+    }
+
+    fn sample_surface(&self, random: [f32; 2]) -> Option<Vector> {
+        self.shape.sample_surface(random)
+    }
+
+    fn get_properties(&self) -> &ShapeProperties {
+        &self.properties
+    }
+}
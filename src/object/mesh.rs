@@ -0,0 +1,382 @@
+use crate::primitive::{bounding_box::BoundingBox, contact::RayContact, ray::Ray, vector::Vector};
+
+use super::{bvh::Bvh, Shape};
+
+const PARALLEL_EPSILON: f32 = 1e-8;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+struct TriangleHit {
+    multiplier: f32,
+    barycentric: [f32; 3],
+}
+
+// Möller–Trumbore, the returned multiplier is in units of the ray direction.
+fn intersect_triangle(ray: &Ray, vertices: [&Vector; 3]) -> Option<TriangleHit> {
+    let edge_1 = vertices[1].minus(vertices[0]);
+    let edge_2 = vertices[2].minus(vertices[0]);
+
+    let p = ray.direction.cross(&edge_2);
+    let determinant = edge_1.dot(&p);
+    if determinant.abs() < PARALLEL_EPSILON {
+        return None;
+    }
+
+    let inverse_determinant = 1.0 / determinant;
+    let s = ray.origin.minus(vertices[0]);
+    let u = s.dot(&p) * inverse_determinant;
+    if u < 0.0 || u > 1.0 {
+        return None;
+    }
+
+    let q = s.cross(&edge_1);
+    let v = ray.direction.dot(&q) * inverse_determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let multiplier = edge_2.dot(&q) * inverse_determinant;
+    if multiplier > 0.0 {
+        Some(TriangleHit {
+            multiplier,
+            barycentric: [1.0 - u - v, u, v],
+        })
+    } else {
+        None
+    }
+}
+
+fn interpolate_uv(uvs: [&[f32; 2]; 3], barycentric: &[f32; 3]) -> [f32; 2] {
+    let mut uv = [0.0; 2];
+    for (index, value) in uv.iter_mut().enumerate() {
+        *value = (0..3).map(|i| uvs[i][index] * barycentric[i]).sum();
+    }
+
+    uv
+}
+
+/*
+ * Directions in which the surface coordinates grow across a triangle, found by solving both edges
+ * for their change in u and v. Triangles without coordinates span the unit square from their first
+ * corner, so their edges are the tangents themselves.
+ */
+fn get_uv_tangents(vertices: [&Vector; 3], uvs: [&[f32; 2]; 3]) -> [Vector; 2] {
+    let edge_1 = vertices[1].minus(vertices[0]);
+    let edge_2 = vertices[2].minus(vertices[0]);
+    let delta_1 = [uvs[1][0] - uvs[0][0], uvs[1][1] - uvs[0][1]];
+    let delta_2 = [uvs[2][0] - uvs[0][0], uvs[2][1] - uvs[0][1]];
+
+    let determinant = delta_1[0] * delta_2[1] - delta_2[0] * delta_1[1];
+    if determinant.abs() < PARALLEL_EPSILON {
+        return [edge_1, edge_2];
+    }
+
+    [
+        edge_1
+            .times(delta_2[1])
+            .minus(&edge_2.times(delta_1[1]))
+            .times(1.0 / determinant),
+        edge_2
+            .times(delta_1[0])
+            .minus(&edge_1.times(delta_2[0]))
+            .times(1.0 / determinant),
+    ]
+}
+
+fn interpolate(values: [&Vector; 3], barycentric: &[f32; 3]) -> Vector {
+    values[0]
+        .times(barycentric[0])
+        .plus(&values[1].times(barycentric[1]))
+        .plus(&values[2].times(barycentric[2]))
+}
+
+// Vertex normals may be wound the other way than the triangle, they're flipped to its side so the
+// contact doesn't reject them as bent through the surface.
+fn get_shading_normal(geometric_normal: Vector, interpolated_normal: Vector) -> Vector {
+    if interpolated_normal.dot(&geometric_normal) < 0.0 {
+        interpolated_normal.times(-1.0)
+    } else {
+        interpolated_normal
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+pub struct TriangleShape {
+    vertices: [Vector; 3],
+    normals: Option<[Vector; 3]>,
+}
+
+impl TriangleShape {
+    pub fn new(a: Vector, b: Vector, c: Vector) -> Self {
+        Self {
+            vertices: [a, b, c],
+            normals: None,
+        }
+    }
+
+    pub fn with_normals(a: Vector, b: Vector, c: Vector, normals: [Vector; 3]) -> Self {
+        Self {
+            vertices: [a, b, c],
+            normals: Some(normals),
+        }
+    }
+}
+
+impl Shape for TriangleShape {
+    fn get_contact<'a>(&self, ray: &'a Ray) -> Option<RayContact<'a>> {
+        let vertices = [&self.vertices[0], &self.vertices[1], &self.vertices[2]];
+        let hit = intersect_triangle(ray, vertices)?;
+
+        let position = interpolate(vertices, &hit.barycentric);
+        let geometric_normal = vertices[1]
+            .minus(vertices[0])
+            .cross(&vertices[2].minus(vertices[0]));
+
+        // Without coordinates of its own, a triangle spans the unit one from its first corner.
+        let uv = [hit.barycentric[1], hit.barycentric[2]];
+
+        let mut contact = RayContact::new(position, geometric_normal, ray)
+            .with_uv(uv)
+            .with_tangents(
+                vertices[1].minus(vertices[0]),
+                vertices[2].minus(vertices[0]),
+            );
+        if let Some(normals) = &self.normals {
+            contact.set_shading_normal(get_shading_normal(
+                geometric_normal,
+                interpolate([&normals[0], &normals[1], &normals[2]], &hit.barycentric),
+            ));
+        }
+        Some(contact)
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        BoundingBox::around_points(&self.vertices)
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[derive(Clone, Copy)]
+pub struct MeshTriangle {
+    pub positions: [usize; 3],
+    pub normals: Option<[usize; 3]>,
+    pub uvs: Option<[usize; 3]>,
+}
+
+pub struct MeshShape {
+    positions: Vec<Vector>,
+    normals: Vec<Vector>,
+    uvs: Vec<[f32; 2]>,
+    triangles: Vec<MeshTriangle>,
+
+    bvh: Bvh,
+}
+
+impl MeshShape {
+    pub fn new(
+        positions: Vec<Vector>,
+        normals: Vec<Vector>,
+        uvs: Vec<[f32; 2]>,
+        triangles: Vec<MeshTriangle>,
+    ) -> Self {
+        let bounding_boxes: Vec<BoundingBox> = triangles
+            .iter()
+            .map(|triangle| {
+                BoundingBox::around_points(&[
+                    positions[triangle.positions[0]],
+                    positions[triangle.positions[1]],
+                    positions[triangle.positions[2]],
+                ])
+            })
+            .collect();
+
+        Self {
+            positions,
+            normals,
+            uvs,
+            triangles,
+
+            bvh: Bvh::build(&bounding_boxes),
+        }
+    }
+
+    fn get_vertices(&self, triangle: &MeshTriangle) -> [&Vector; 3] {
+        [
+            &self.positions[triangle.positions[0]],
+            &self.positions[triangle.positions[1]],
+            &self.positions[triangle.positions[2]],
+        ]
+    }
+}
+
+impl Shape for MeshShape {
+    fn get_contact<'a>(&self, ray: &'a Ray) -> Option<RayContact<'a>> {
+        let direction_length = ray.direction.len();
+
+        let mut closest_hit: Option<(usize, TriangleHit)> = None;
+        self.bvh.traverse(ray, f32::MAX, |triangle_id| {
+            let hit = intersect_triangle(ray, self.get_vertices(&self.triangles[triangle_id]))?;
+            let is_closer = closest_hit
+                .as_ref()
+                .map_or(true, |(_, closest)| hit.multiplier < closest.multiplier);
+
+            if is_closer {
+                let distance = hit.multiplier * direction_length;
+                closest_hit = Some((triangle_id, hit));
+                Some(distance)
+            } else {
+                None
+            }
+        });
+
+        let (triangle_id, hit) = closest_hit?;
+        let triangle = &self.triangles[triangle_id];
+        let vertices = self.get_vertices(triangle);
+
+        let position = interpolate(vertices, &hit.barycentric);
+        let geometric_normal = vertices[1]
+            .minus(vertices[0])
+            .cross(&vertices[2].minus(vertices[0]));
+        let (uv, tangents) = match triangle.uvs {
+            Some(uvs) => {
+                let uvs = [&self.uvs[uvs[0]], &self.uvs[uvs[1]], &self.uvs[uvs[2]]];
+                (
+                    interpolate_uv(uvs, &hit.barycentric),
+                    get_uv_tangents(vertices, uvs),
+                )
+            }
+            None => (
+                [hit.barycentric[1], hit.barycentric[2]],
+                [
+                    vertices[1].minus(vertices[0]),
+                    vertices[2].minus(vertices[0]),
+                ],
+            ),
+        };
+
+        let mut contact = RayContact::new(position, geometric_normal, ray)
+            .with_uv(uv)
+            .with_tangents(tangents[0], tangents[1]);
+        if let Some(normals) = triangle.normals {
+            contact.set_shading_normal(get_shading_normal(
+                geometric_normal,
+                interpolate(
+                    [
+                        &self.normals[normals[0]],
+                        &self.normals[normals[1]],
+                        &self.normals[normals[2]],
+                    ],
+                    &hit.barycentric,
+                ),
+            ));
+        }
+        Some(contact)
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        self.bvh.get_bounding_box()
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        object::{
+            testing::{assert_vector_close, ray},
+            transformed::TransformedShape,
+        },
+        primitive::transform::Transform,
+    };
+
+    // Triangle in the floor facing up, with vertex normals leaning towards x.
+    fn leaning_triangle() -> TriangleShape {
+        let normal = Vector::new(1.0, 1.0, 0.0);
+        TriangleShape::with_normals(
+            Vector::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+            Vector::new(1.0, 0.0, 0.0),
+            [normal, normal, normal],
+        )
+    }
+
+    fn leaning_mesh(normal: Vector) -> MeshShape {
+        MeshShape::new(
+            vec![
+                Vector::new(0.0, 0.0, 0.0),
+                Vector::new(0.0, 0.0, 1.0),
+                Vector::new(1.0, 0.0, 0.0),
+            ],
+            vec![normal],
+            Vec::new(),
+            vec![MeshTriangle {
+                positions: [0, 1, 2],
+                normals: Some([0, 0, 0]),
+                uvs: None,
+            }],
+        )
+    }
+
+    #[test]
+    fn shades_with_vertex_normals_around_geometric_normal() {
+        let leaning = Vector::new(1.0, 1.0, 0.0).normalized();
+        let triangle = leaning_triangle();
+        let mesh = leaning_mesh(Vector::new(1.0, 1.0, 0.0));
+        let shapes: [&dyn Shape; 2] = [&triangle, &mesh];
+
+        for shape in shapes.iter() {
+            let from_above = ray(Vector::new(0.25, 2.0, 0.25), Vector::y().times(-1.0));
+            let contact = shape.get_contact(&from_above).expect("the ray should hit");
+            assert_vector_close(&contact.get_normal(), &Vector::y());
+            assert_vector_close(&contact.get_shading_normal(), &leaning);
+            assert!(!contact.is_from_inside());
+
+            // The side the ray is on follows the triangle, not the leaning normal.
+            let grazing = ray(Vector::new(-1.0, 0.5, 0.25), Vector::new(1.25, -0.5, 0.0));
+            let contact = shape.get_contact(&grazing).expect("the ray should hit");
+            assert!(!contact.is_from_inside());
+            assert!(contact.is_transmission(&Vector::new(0.0, -1.0, 0.0)));
+
+            let from_below = ray(Vector::new(0.25, -2.0, 0.25), Vector::y());
+            let contact = shape.get_contact(&from_below).expect("the ray should hit");
+            assert!(contact.is_from_inside());
+            assert_vector_close(&contact.get_facing_normal(), &leaning.times(-1.0));
+        }
+    }
+
+    #[test]
+    fn flips_vertex_normals_wound_the_other_way() {
+        let mesh = leaning_mesh(Vector::new(-1.0, -1.0, 0.0));
+        let from_above = ray(Vector::new(0.25, 2.0, 0.25), Vector::y().times(-1.0));
+        let contact = mesh.get_contact(&from_above).expect("the ray should hit");
+        assert_vector_close(&contact.get_normal(), &Vector::y());
+        assert_vector_close(
+            &contact.get_shading_normal(),
+            &Vector::new(1.0, 1.0, 0.0).normalized(),
+        );
+    }
+
+    #[test]
+    fn keeps_vertex_normals_of_transformed_mesh() {
+        // Stretched along x and turned a quarter around z, the floor becomes a wall facing -x.
+        let transform = Transform::scaling(&Vector::new(2.0, 1.0, 1.0)).then(&Transform::rotation(
+            &Vector::z(),
+            std::f32::consts::FRAC_PI_2,
+        ));
+        let shape = TransformedShape::new(leaning_mesh(Vector::new(1.0, 1.0, 0.0)), transform);
+
+        let incoming = ray(Vector::new(-3.0, 0.25, 0.25), Vector::x());
+        let contact = shape.get_contact(&incoming).expect("the ray should hit");
+        assert_vector_close(&contact.get_normal(), &Vector::x().times(-1.0));
+        // (1, 1, 0) through the inverse transpose of the stretch is (1/2, 1, 0), then turned.
+        assert_vector_close(
+            &contact.get_shading_normal(),
+            &Vector::new(-1.0, 0.5, 0.0).normalized(),
+        );
+        assert!(!contact.is_from_inside());
+    }
+}
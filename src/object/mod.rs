@@ -1,36 +1,82 @@
-pub mod cube;
+mod axial;
+pub mod box_shape;
+pub mod bvh;
+pub mod cone;
+pub mod cylinder;
+pub mod disk;
+pub mod infinite_plane;
+pub mod instance;
+pub mod mesh;
+pub mod obj;
 pub mod plane;
 pub mod sphere;
+#[cfg(test)]
+mod testing;
+pub mod torus;
+pub mod transformed;
 
-use crate::primitive::{color::Color, contact::RayContact, ray::Ray};
+use std::sync::Arc;
 
-/* ------------------------------------------------------------ */
+use crate::{
+    material::{medium::Medium, Material},
+    primitive::{bounding_box::BoundingBox, contact::RayContact, ray::Ray, vector::Vector},
+};
 
-#[derive(Clone, Copy)]
-pub enum ShapeType {
-    Emitter,
-    Reflector {
-        transparency: f32,
-        roughness: f32,
-        density: f32,
-    },
-}
+/* ------------------------------------------------------------ */
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct ShapeProperties {
-    pub color: Color,
-    pub shape_type: ShapeType,
+    pub material: Arc<dyn Material>,
+    // Fills the inside of closed shapes whose material lets rays in.
+    pub medium: Option<Medium>,
 }
 
 /* ------------------------------------------------------------ */
 pub trait Object: Sync + Send {
     fn get_contact<'a>(&self, ray: &'a Ray) -> Option<RayContact<'a>>;
 
+    fn get_bounding_box(&self) -> BoundingBox;
+
+    fn get_surface_area(&self) -> Option<f32>;
+
+    fn sample_surface(&self, random: [f32; 2]) -> Option<Vector>;
+
     fn get_properties(&self) -> &ShapeProperties;
 }
 
 pub trait Shape: Sync + Send {
     fn get_contact<'a>(&self, ray: &'a Ray) -> Option<RayContact<'a>>;
+
+    fn get_bounding_box(&self) -> BoundingBox;
+
+    // Shapes that can be sampled as area lights report their area and pick points uniformly on
+    // it, the others are only reached by the rays that happen to hit them.
+    fn get_surface_area(&self) -> Option<f32> {
+        None
+    }
+
+    fn sample_surface(&self, _random: [f32; 2]) -> Option<Vector> {
+        None
+    }
+}
+
+// Shared shapes are shapes too, which lets them be wrapped and placed like owned ones.
+impl<S: Shape + ?Sized> Shape for Arc<S> {
+    fn get_contact<'a>(&self, ray: &'a Ray) -> Option<RayContact<'a>> {
+        self.as_ref().get_contact(ray)
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        self.as_ref().get_bounding_box()
+    }
+
+    fn get_surface_area(&self) -> Option<f32> {
+        self.as_ref().get_surface_area()
+    }
+
+    fn sample_surface(&self, random: [f32; 2]) -> Option<Vector> {
+        self.as_ref().sample_surface(random)
+    }
 }
 
 /* ------------------------------------------------------------ */
@@ -51,6 +97,18 @@ impl<C: Shape + Sync + Send> Object for PhysicalObject<C> {
         self.shape.get_contact(ray)
     }
 
+    fn get_bounding_box(&self) -> BoundingBox {
+        self.shape.get_bounding_box()
+    }
+
+    fn get_surface_area(&self) -> Option<f32> {
+        self.shape.get_surface_area()
+    }
+
+    fn sample_surface(&self, random: [f32; 2]) -> Option<Vector> {
+        self.shape.sample_surface(random)
+    }
+
     fn get_properties(&self) -> &ShapeProperties {
         &self.properties
     }
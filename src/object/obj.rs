@@ -0,0 +1,352 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::primitive::vector::Vector;
+
+use super::mesh::{MeshShape, MeshTriangle};
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io(PathBuf, io::Error),
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io(path, error) => write!(f, "could not read {}: {}", path.display(), error),
+            ObjError::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+        }
+    }
+}
+
+impl Error for ObjError {}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+pub struct ObjGroup {
+    pub material_name: Option<String>,
+    pub mesh: MeshShape,
+}
+
+#[derive(Clone, Copy)]
+struct FaceVertex {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>,
+}
+
+// Every `usemtl` group only keeps the vertex data its own faces reference.
+#[derive(Default)]
+struct GroupBuilder {
+    positions: Vec<Vector>,
+    normals: Vec<Vector>,
+    uvs: Vec<[f32; 2]>,
+    triangles: Vec<MeshTriangle>,
+
+    position_ids: HashMap<usize, usize>,
+    normal_ids: HashMap<usize, usize>,
+    uv_ids: HashMap<usize, usize>,
+}
+
+impl GroupBuilder {
+    fn add_triangle(&mut self, vertices: [FaceVertex; 3], data: &ObjData) {
+        let mut positions = [0; 3];
+        let mut normals = [0; 3];
+        let mut uvs = [0; 3];
+        for (i, vertex) in vertices.iter().enumerate() {
+            positions[i] = remap(
+                vertex.position,
+                &mut self.position_ids,
+                &mut self.positions,
+                &data.positions,
+            );
+            if let Some(normal) = vertex.normal {
+                normals[i] = remap(
+                    normal,
+                    &mut self.normal_ids,
+                    &mut self.normals,
+                    &data.normals,
+                );
+            }
+            if let Some(uv) = vertex.uv {
+                uvs[i] = remap(uv, &mut self.uv_ids, &mut self.uvs, &data.uvs);
+            }
+        }
+
+        let has_normals = vertices.iter().all(|vertex| vertex.normal.is_some());
+        let has_uvs = vertices.iter().all(|vertex| vertex.uv.is_some());
+        self.triangles.push(MeshTriangle {
+            positions,
+            normals: if has_normals { Some(normals) } else { None },
+            uvs: if has_uvs { Some(uvs) } else { None },
+        });
+    }
+
+    fn build(self) -> MeshShape {
+        MeshShape::new(self.positions, self.normals, self.uvs, self.triangles)
+    }
+}
+
+fn remap<T: Copy>(
+    id: usize,
+    ids: &mut HashMap<usize, usize>,
+    values: &mut Vec<T>,
+    source: &[T],
+) -> usize {
+    *ids.entry(id).or_insert_with(|| {
+        values.push(source[id]);
+        values.len() - 1
+    })
+}
+
+#[derive(Default)]
+struct ObjData {
+    positions: Vec<Vector>,
+    normals: Vec<Vector>,
+    uvs: Vec<[f32; 2]>,
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+pub fn load_obj(path: &Path) -> Result<Vec<ObjGroup>, ObjError> {
+    let source =
+        fs::read_to_string(path).map_err(|error| ObjError::Io(path.to_path_buf(), error))?;
+
+    let parsed = parse_obj(&source).map_err(|(line, message)| ObjError::Parse {
+        path: path.to_path_buf(),
+        line,
+        message,
+    })?;
+
+    for (line, keyword) in parsed.skipped_statements {
+        eprintln!(
+            "warning: {}:{}: skipped unsupported `{}` statements",
+            path.display(),
+            line,
+            keyword
+        );
+    }
+    Ok(parsed.groups)
+}
+
+struct ParsedObj {
+    groups: Vec<ObjGroup>,
+    // Kinds of statements that were skipped, with the line each first appeared on.
+    skipped_statements: Vec<(usize, String)>,
+}
+
+// Statements for things that aren't rendered, such as curves or free-form surfaces, are skipped
+// rather than failing the whole file.
+fn parse_obj(source: &str) -> Result<ParsedObj, (usize, String)> {
+    let mut data = ObjData::default();
+    let mut skipped_statements: Vec<(usize, String)> = Vec::new();
+
+    let mut group_names: Vec<Option<String>> = vec![None];
+    let mut groups: Vec<GroupBuilder> = vec![GroupBuilder::default()];
+    let mut current_group = 0;
+
+    for (line_index, line) in source.lines().enumerate() {
+        let line_number = line_index + 1;
+        let line = line.split('#').next().unwrap_or("").trim();
+
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let arguments: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => data
+                .positions
+                .push(parse_vector(&arguments).map_err(|message| (line_number, message))?),
+            "vn" => data.normals.push(
+                parse_vector(&arguments)
+                    .map_err(|message| (line_number, message))?
+                    .normalized(),
+            ),
+            "vt" => {
+                let values =
+                    parse_floats(&arguments, 1).map_err(|message| (line_number, message))?;
+                data.uvs
+                    .push([values[0], values.get(1).cloned().unwrap_or(0.0)]);
+            }
+            "f" => {
+                if arguments.len() < 3 {
+                    return Err((
+                        line_number,
+                        String::from("a face needs at least 3 vertices"),
+                    ));
+                }
+
+                let mut vertices = Vec::with_capacity(arguments.len());
+                for argument in &arguments {
+                    vertices.push(
+                        parse_face_vertex(argument, &data)
+                            .map_err(|message| (line_number, message))?,
+                    );
+                }
+
+                // Polygons are split into a fan around their first vertex.
+                for i in 1..vertices.len() - 1 {
+                    groups[current_group]
+                        .add_triangle([vertices[0], vertices[i], vertices[i + 1]], &data);
+                }
+            }
+            "usemtl" => {
+                let name = arguments.join(" ");
+                current_group = match group_names
+                    .iter()
+                    .position(|group_name| group_name.as_deref() == Some(name.as_str()))
+                {
+                    Some(index) => index,
+                    None => {
+                        group_names.push(Some(name));
+                        groups.push(GroupBuilder::default());
+                        groups.len() - 1
+                    }
+                };
+            }
+            "o" | "g" | "s" | "mtllib" | "l" | "p" => (),
+            _ => {
+                if !skipped_statements
+                    .iter()
+                    .any(|(_, skipped_keyword)| skipped_keyword == keyword)
+                {
+                    skipped_statements.push((line_number, String::from(keyword)));
+                }
+            }
+        }
+    }
+
+    let groups = group_names
+        .into_iter()
+        .zip(groups.into_iter())
+        .filter(|(_, group)| !group.triangles.is_empty())
+        .map(|(material_name, group)| ObjGroup {
+            material_name,
+            mesh: group.build(),
+        })
+        .collect();
+    Ok(ParsedObj {
+        groups,
+        skipped_statements,
+    })
+}
+
+fn parse_floats(arguments: &[&str], min_count: usize) -> Result<Vec<f32>, String> {
+    if arguments.len() < min_count {
+        return Err(format!(
+            "expected at least {} values, got {}",
+            min_count,
+            arguments.len()
+        ));
+    }
+
+    arguments
+        .iter()
+        .map(|argument| {
+            argument
+                .parse()
+                .map_err(|_| format!("invalid number `{}`", argument))
+        })
+        .collect()
+}
+
+fn parse_vector(arguments: &[&str]) -> Result<Vector, String> {
+    let values = parse_floats(arguments, 3)?;
+    Ok(Vector::new(values[0], values[1], values[2]))
+}
+
+fn parse_face_vertex(argument: &str, data: &ObjData) -> Result<FaceVertex, String> {
+    let mut parts = argument.split('/');
+
+    let position = parse_index(parts.next(), data.positions.len(), "position")?
+        .ok_or_else(|| format!("face vertex `{}` has no position", argument))?;
+    let uv = parse_index(parts.next(), data.uvs.len(), "texture coordinate")?;
+    let normal = parse_index(parts.next(), data.normals.len(), "normal")?;
+
+    Ok(FaceVertex {
+        position,
+        uv,
+        normal,
+    })
+}
+
+// OBJ indices start at 1, negative indices count back from the last element defined so far.
+fn parse_index(part: Option<&str>, count: usize, name: &str) -> Result<Option<usize>, String> {
+    let part = match part {
+        Some(part) if !part.is_empty() => part,
+        _ => return Ok(None),
+    };
+
+    let index: i64 = part
+        .parse()
+        .map_err(|_| format!("invalid {} index `{}`", name, part))?;
+    let resolved = if index < 0 {
+        count as i64 + index
+    } else {
+        index - 1
+    };
+
+    if resolved >= 0 && resolved < count as i64 {
+        Ok(Some(resolved as usize))
+    } else {
+        Err(format!(
+            "{} index {} is out of range, {} defined so far",
+            name, index, count
+        ))
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_unsupported_statements_once_each() {
+        let source = "\
+            v 0 0 0\n\
+            v 1 0 0\n\
+            v 0 1 0\n\
+            vp 0.5\n\
+            cstype bspline\n\
+            vp 0.25\n\
+            s off\n\
+            l 1 2\n\
+            f 1 2 3\n";
+
+        let parsed = parse_obj(source).expect("the file should load");
+        assert_eq!(parsed.groups.len(), 1);
+        assert_eq!(
+            parsed.skipped_statements,
+            vec![(4, String::from("vp")), (5, String::from("cstype"))]
+        );
+    }
+
+    #[test]
+    fn still_rejects_malformed_supported_statements() {
+        assert_eq!(parse_obj("v 0 0\n").err().map(|(line, _)| line), Some(1));
+        assert_eq!(
+            parse_obj("v 0 0 0\nf 1 2\n").err().map(|(line, _)| line),
+            Some(2)
+        );
+    }
+}
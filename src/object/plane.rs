@@ -1,4 +1,4 @@
-use crate::primitive::{contact::RayContact, ray::Ray, vector::Vector};
+use crate::primitive::{bounding_box::BoundingBox, contact::RayContact, ray::Ray, vector::Vector};
 
 use super::Shape;
 
@@ -95,11 +95,17 @@ impl Shape for PlaneShape {
                 if from_center_length.len() < self.half_length {
                     let from_center_width = contact_from_center.project_onto(&self.width_vector);
                     if from_center_width.len() < self.half_width {
-                        Some(RayContact::new(
-                            contact_point,
-                            Vector::from(&self.normal),
-                            ray,
-                        ))
+                        let uv = [
+                            0.5 - contact_from_center.dot(&self.width_vector)
+                                / (2.0 * self.half_width),
+                            0.5 + contact_from_center.dot(&self.length_vector)
+                                / (2.0 * self.half_length),
+                        ];
+                        Some(
+                            RayContact::new(contact_point, Vector::from(&self.normal), ray)
+                                .with_uv(uv)
+                                .with_tangents(self.width_vector, self.length_vector.times(-1.0)),
+                        )
                     } else {
                         None
                     }
@@ -113,4 +119,31 @@ impl Shape for PlaneShape {
             None
         }
     }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        let length_offset = self.length_vector.times(self.half_length);
+        let width_offset = self.width_vector.times(self.half_width);
+
+        BoundingBox::around_points(&[
+            self.center.plus(&length_offset).plus(&width_offset),
+            self.center.plus(&length_offset).minus(&width_offset),
+            self.center.minus(&length_offset).plus(&width_offset),
+            self.center.minus(&length_offset).minus(&width_offset),
+        ])
+    }
+
+    fn get_surface_area(&self) -> Option<f32> {
+        Some(4.0 * self.half_length * self.half_width)
+    }
+
+    fn sample_surface(&self, random: [f32; 2]) -> Option<Vector> {
+        let length_offset = self
+            .length_vector
+            .times((2.0 * random[0] - 1.0) * self.half_length);
+        let width_offset = self
+            .width_vector
+            .times((2.0 * random[1] - 1.0) * self.half_width);
+
+        Some(self.center.plus(&length_offset).plus(&width_offset))
+    }
 }
@@ -1,6 +1,14 @@
-use crate::primitive::{contact::RayContact, ray::Ray, vector::Vector};
+use std::f32::consts::PI;
 
-use super::Shape;
+use crate::{
+    material::sampling::sample_uniform_sphere,
+    primitive::{bounding_box::BoundingBox, contact::RayContact, ray::Ray, vector::Vector},
+};
+
+use super::{
+    axial::{get_azimuth, get_azimuth_tangent},
+    Shape,
+};
 
 pub struct SphereShape {
     position: Vector,
@@ -41,11 +49,18 @@ impl Shape for SphereShape {
                 let position = ray.origin.plus(&ray.direction.times(multiplier));
                 let normal = position.minus(&self.position).normalized();
 
-                Some(RayContact::new(
-                    normal.times(self.radius).plus(&self.position),
-                    normal,
-                    ray,
-                ))
+                // Longitude around the y axis and latitude from the bottom pole to the top one.
+                let uv = [
+                    get_azimuth(&normal),
+                    0.5 + normal.y.clamp(-1.0, 1.0).asin() / PI,
+                ];
+                // Towards the top pole along the meridian.
+                let up = Vector::y().minus(&normal.times(normal.y));
+                Some(
+                    RayContact::new(normal.times(self.radius).plus(&self.position), normal, ray)
+                        .with_uv(uv)
+                        .with_tangents(get_azimuth_tangent(&normal), up),
+                )
             } else {
                 None
             }
@@ -53,4 +68,19 @@ impl Shape for SphereShape {
             None
         }
     }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        BoundingBox::around_point(&self.position, self.radius)
+    }
+
+    fn get_surface_area(&self) -> Option<f32> {
+        Some(4.0 * PI * self.radius * self.radius)
+    }
+
+    fn sample_surface(&self, random: [f32; 2]) -> Option<Vector> {
+        Some(
+            self.position
+                .plus(&sample_uniform_sphere(random).times(self.radius)),
+        )
+    }
 }
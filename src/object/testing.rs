@@ -0,0 +1,89 @@
+// Helpers shared by the shapes' tests.
+
+use crate::{
+    object::Shape,
+    primitive::{contact::RayContact, ray::Ray, vector::Vector},
+};
+
+const TOLERANCE: f32 = 1e-4;
+
+pub fn ray(origin: Vector, direction: Vector) -> Ray {
+    Ray { origin, direction }
+}
+
+pub fn assert_close(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() <= TOLERANCE * expected.abs().max(1.0),
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+pub fn assert_vector_close(actual: &Vector, expected: &Vector) {
+    assert!(
+        actual.distance_to(expected) <= TOLERANCE * expected.len().max(1.0),
+        "expected {:?}, got {:?}",
+        expected,
+        actual
+    );
+}
+
+pub fn assert_uv_in_range(contact: &RayContact) {
+    let uv = contact.get_uv();
+    assert!(
+        uv.iter().all(|value| (0.0..=1.0).contains(value)),
+        "surface coordinates {:?} out of range",
+        uv
+    );
+}
+
+pub fn assert_finite(contact: &RayContact) {
+    let position = contact.get_position();
+    let normal = contact.get_normal();
+    assert!(
+        [position.x, position.y, position.z, normal.x, normal.y, normal.z]
+            .iter()
+            .chain(contact.get_uv().iter())
+            .all(|value| value.is_finite()),
+        "contact at {:?} with normal {:?} isn't finite",
+        position,
+        normal
+    );
+}
+
+// Points the shape samples for an even grid of random pairs.
+pub fn sample_grid(shape: &dyn Shape) -> Vec<Vector> {
+    const SIZE: usize = 64;
+    let mut points = Vec::with_capacity(SIZE * SIZE);
+    for i in 0..SIZE {
+        for j in 0..SIZE {
+            let random = [
+                (i as f32 + 0.5) / SIZE as f32,
+                (j as f32 + 0.5) / SIZE as f32,
+            ];
+            points.push(
+                shape
+                    .sample_surface(random)
+                    .expect("the shape should be sampled"),
+            );
+        }
+    }
+
+    points
+}
+
+/*
+ * Sampling is uniform over the surface, with a pdf of one over its area, when the share of the
+ * samples landing in a region matches the share of the area the region covers.
+ */
+pub fn assert_sampled_share(points: &[Vector], is_in_region: impl Fn(&Vector) -> bool, share: f32) {
+    let count = points.iter().filter(|point| is_in_region(point)).count();
+    let sampled_share = count as f32 / points.len() as f32;
+    assert!(
+        (sampled_share - share).abs() < 0.02,
+        "expected {} of the samples in the region, got {}",
+        share,
+        sampled_share
+    );
+}
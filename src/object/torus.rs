@@ -0,0 +1,389 @@
+use std::f32::consts::PI;
+
+use crate::primitive::{
+    bounding_box::BoundingBox, contact::RayContact, ray::Ray, transform::Transform, vector::Vector,
+};
+
+use super::{
+    axial::{get_azimuth, get_azimuth_tangent},
+    Shape,
+};
+
+// Newton steps that polish each root of the closed-form solution, which loses digits to rounding.
+const POLISH_STEP_COUNT: usize = 2;
+const SAMPLING_STEP_COUNT: usize = 8;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Ring around `axis` through `center`, the tube's center line runs `major_radius` from the axis.
+pub struct TorusShape {
+    placement: Transform,
+    major_radius: f32,
+    minor_radius: f32,
+}
+
+impl TorusShape {
+    pub fn new(center: Vector, axis: Vector, major_radius: f32, minor_radius: f32) -> Self {
+        Self {
+            placement: Transform::placing(&center, &axis),
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+impl Shape for TorusShape {
+    fn get_contact<'a>(&self, ray: &'a Ray) -> Option<RayContact<'a>> {
+        let local_ray = self.placement.apply_inverse_to_ray(ray);
+
+        /*
+         * Points on the torus satisfy (|p|² + R² - r²)² = 4R²(x² + z²). Along the ray that's a
+         * quartic in the distance, solved in double precision with a normalized direction since
+         * the coefficients grow with the fourth power of the distances.
+         */
+        let length = local_ray.direction.len() as f64;
+        let origin = [
+            local_ray.origin.x as f64,
+            local_ray.origin.y as f64,
+            local_ray.origin.z as f64,
+        ];
+        let direction = [
+            local_ray.direction.x as f64 / length,
+            local_ray.direction.y as f64 / length,
+            local_ray.direction.z as f64 / length,
+        ];
+
+        let major_sqr = (self.major_radius as f64).powi(2);
+        let minor_sqr = (self.minor_radius as f64).powi(2);
+
+        let e = origin[0] * direction[0] + origin[1] * direction[1] + origin[2] * direction[2];
+        let f = origin.iter().map(|value| value * value).sum::<f64>() + major_sqr - minor_sqr;
+        let g = direction[0] * direction[0] + direction[2] * direction[2];
+        let h = origin[0] * direction[0] + origin[2] * direction[2];
+        let k = origin[0] * origin[0] + origin[2] * origin[2];
+
+        let roots = solve_quartic([
+            1.0,
+            4.0 * e,
+            4.0 * e * e + 2.0 * f - 4.0 * major_sqr * g,
+            4.0 * e * f - 8.0 * major_sqr * h,
+            f * f - 4.0 * major_sqr * k,
+        ]);
+        let distance = roots
+            .into_iter()
+            .filter(|root| *root > 0.0)
+            .fold(f64::INFINITY, f64::min);
+        if !distance.is_finite() {
+            return None;
+        }
+
+        let multiplier = (distance / length) as f32;
+        let position = local_ray
+            .origin
+            .plus(&local_ray.direction.times(multiplier));
+
+        // The normal points away from the nearest point on the tube's center line.
+        let radial = position.x.hypot(position.z);
+        let ring_point = if radial > 0.0 {
+            Vector::new(position.x, 0.0, position.z).times(self.major_radius / radial)
+        } else {
+            Vector::new(self.major_radius, 0.0, 0.0)
+        };
+        let normal = position.minus(&ring_point).normalized();
+        let tube_angle = normal.y.atan2(radial - self.major_radius);
+        // Around the tube the surface turns from the outward direction towards the axis.
+        let outward = ring_point.normalized();
+        let around_tube = Vector::y()
+            .times(normal.dot(&outward))
+            .minus(&outward.times(normal.y));
+
+        Some(
+            RayContact::new(
+                self.placement
+                    .apply_to_point(&ring_point.plus(&normal.times(self.minor_radius))),
+                self.placement.apply_to_direction(&normal),
+                ray,
+            )
+            .with_uv([
+                get_azimuth(&position),
+                (tube_angle / (2.0 * PI)).rem_euclid(1.0),
+            ])
+            .with_tangents(
+                self.placement
+                    .apply_to_direction(&get_azimuth_tangent(&position)),
+                self.placement.apply_to_direction(&around_tube),
+            ),
+        )
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        let outer = self.major_radius + self.minor_radius;
+        let extent = Vector::new(outer, self.minor_radius, outer);
+        self.placement
+            .apply_to_bounding_box(&BoundingBox::new(extent.times(-1.0), extent))
+    }
+
+    fn get_surface_area(&self) -> Option<f32> {
+        Some(4.0 * PI * PI * self.major_radius * self.minor_radius)
+    }
+
+    fn sample_surface(&self, random: [f32; 2]) -> Option<Vector> {
+        /*
+         * The outer half of the tube is wider than the inner one, the area at a tube angle θ is
+         * proportional to R + r·cos θ. Its integral R·θ + r·sin θ is inverted by Newton's method,
+         * which converges from any start since it only grows.
+         */
+        let (major, minor) = (self.major_radius, self.minor_radius);
+        let target = 2.0 * PI * major * random[0];
+        let mut theta = 2.0 * PI * random[0];
+        for _ in 0..SAMPLING_STEP_COUNT {
+            let error = major * theta + minor * theta.sin() - target;
+            theta = (theta - error / (major + minor * theta.cos())).clamp(0.0, 2.0 * PI);
+        }
+
+        let phi = 2.0 * PI * random[1];
+        let radial = major + minor * theta.cos();
+        let local = Vector::new(radial * phi.cos(), minor * theta.sin(), radial * phi.sin());
+
+        Some(self.placement.apply_to_point(&local))
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Real roots of a quartic polynomial with the given coefficients, highest power first.
+fn solve_quartic(coefficients: [f64; 5]) -> Vec<f64> {
+    let [a, b, c, d, e] = coefficients;
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+
+    // Depressed to y⁴ + p·y² + q·y + r by substituting x = y - b/4.
+    let shift = -b / 4.0;
+    let p = c - 3.0 * b * b / 8.0;
+    let q = d - b * c / 2.0 + b * b * b / 8.0;
+    let r = e - b * d / 4.0 + b * b * c / 16.0 - 3.0 * b * b * b * b / 256.0;
+
+    let mut roots = Vec::with_capacity(4);
+    if q.abs() < 1e-12 {
+        // Biquadratic, a quadratic in y².
+        let discriminant = p * p - 4.0 * r;
+        if discriminant >= 0.0 {
+            for y_sqr in [
+                (-p + discriminant.sqrt()) / 2.0,
+                (-p - discriminant.sqrt()) / 2.0,
+            ] {
+                if y_sqr >= 0.0 {
+                    roots.push(y_sqr.sqrt() + shift);
+                    roots.push(-y_sqr.sqrt() + shift);
+                }
+            }
+        }
+    } else {
+        // Ferrari's method, splitting the quartic into two quadratics with a root of the resolvent
+        // cubic m³ + p·m² + (p²/4 - r)·m - q²/8. It always has a positive root since q isn't zero.
+        let m = solve_cubic_largest(p, p * p / 4.0 - r, -q * q / 8.0);
+        if m <= 0.0 {
+            return roots;
+        }
+
+        let sqrt_2m = (2.0 * m).sqrt();
+        for sign in [-1.0, 1.0] {
+            let discriminant = -(2.0 * p + 2.0 * m + sign * 2.0 * q / sqrt_2m);
+            if discriminant >= 0.0 {
+                let sqrt = discriminant.sqrt();
+                roots.push((sign * sqrt_2m + sqrt) / 2.0 + shift);
+                roots.push((sign * sqrt_2m - sqrt) / 2.0 + shift);
+            }
+        }
+    }
+
+    for root in &mut roots {
+        for _ in 0..POLISH_STEP_COUNT {
+            let value = (((*root + b) * *root + c) * *root + d) * *root + e;
+            let slope = ((4.0 * *root + 3.0 * b) * *root + 2.0 * c) * *root + d;
+            if slope != 0.0 {
+                *root -= value / slope;
+            }
+        }
+    }
+
+    roots
+}
+
+// Largest real root of m³ + a·m² + b·m + c.
+fn solve_cubic_largest(a: f64, b: f64, c: f64) -> f64 {
+    let p = b - a * a / 3.0;
+    let q = 2.0 * a * a * a / 27.0 - a * b / 3.0 + c;
+    let shift = -a / 3.0;
+
+    let discriminant = q * q / 4.0 + p * p * p / 27.0;
+    let root = if discriminant > 0.0 {
+        let sqrt = discriminant.sqrt();
+        (-q / 2.0 + sqrt).cbrt() + (-q / 2.0 - sqrt).cbrt() + shift
+    } else if p == 0.0 {
+        shift
+    } else {
+        let angle = (3.0 * q / (2.0 * p) * (-3.0 / p).sqrt())
+            .clamp(-1.0, 1.0)
+            .acos()
+            / 3.0;
+        2.0 * (-p / 3.0).sqrt() * angle.cos() + shift
+    };
+
+    // One Newton step, the closed form loses precision when the roots are close together.
+    let value = ((root + a) * root + b) * root + c;
+    let slope = (3.0 * root + 2.0 * a) * root + b;
+    if slope != 0.0 {
+        root - value / slope
+    } else {
+        root
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::testing::{
+        assert_close, assert_finite, assert_sampled_share, assert_uv_in_range, assert_vector_close,
+        ray, sample_grid,
+    };
+
+    // Ring of radius 1 around the y axis, with a tube of radius 0.25.
+    fn ring() -> TorusShape {
+        TorusShape::new(Vector::new(0.0, 0.0, 0.0), Vector::y(), 1.0, 0.25)
+    }
+
+    fn sorted(mut roots: Vec<f64>) -> Vec<f64> {
+        roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        roots
+    }
+
+    #[test]
+    fn solves_quartics() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        let roots = sorted(solve_quartic([1.0, -10.0, 35.0, -50.0, 24.0]));
+        assert_eq!(roots.len(), 4);
+        for (root, expected) in roots.iter().zip(&[1.0, 2.0, 3.0, 4.0]) {
+            assert!((root - expected).abs() < 1e-9, "{:?}", roots);
+        }
+
+        // (x² - 1)(x² - 4), which has no odd powers.
+        let roots = sorted(solve_quartic([1.0, 0.0, -5.0, 0.0, 4.0]));
+        for (root, expected) in roots.iter().zip(&[-2.0, -1.0, 1.0, 2.0]) {
+            assert!((root - expected).abs() < 1e-9, "{:?}", roots);
+        }
+
+        // x⁴ + 1
+        assert!(solve_quartic([1.0, 0.0, 0.0, 0.0, 1.0]).is_empty());
+    }
+
+    #[test]
+    fn hits_nearest_tube_from_outside() {
+        let shape = ring();
+        let across = ray(Vector::new(-5.0, 0.0, 0.0), Vector::x());
+        let contact = shape.get_contact(&across).expect("the ray should hit");
+        assert_close(contact.get_distance_from_origin(), 3.75);
+        assert_vector_close(&contact.get_normal(), &Vector::x().times(-1.0));
+        assert!(!contact.is_from_inside());
+
+        let from_above = ray(Vector::new(0.0, 5.0, -1.0), Vector::y().times(-1.0));
+        let contact = shape.get_contact(&from_above).expect("the ray should hit");
+        assert_close(contact.get_distance_from_origin(), 4.75);
+        assert_vector_close(&contact.get_normal(), &Vector::y());
+    }
+
+    #[test]
+    fn passes_through_the_hole() {
+        let shape = ring();
+        let down_the_axis = ray(Vector::new(0.0, 5.0, 0.0), Vector::y().times(-1.0));
+        assert!(shape.get_contact(&down_the_axis).is_none());
+
+        let slanted = ray(Vector::new(-0.3, 5.0, 0.2), Vector::new(0.1, -1.0, -0.05));
+        assert!(shape.get_contact(&slanted).is_none());
+
+        // From the middle of the hole the inner wall is 0.75 away, facing the axis.
+        let outwards = ray(Vector::new(0.0, 0.0, 0.0), Vector::x());
+        let contact = shape.get_contact(&outwards).expect("the ray should hit");
+        assert_close(contact.get_distance_from_origin(), 0.75);
+        assert_vector_close(&contact.get_normal(), &Vector::x().times(-1.0));
+        assert!(!contact.is_from_inside());
+    }
+
+    #[test]
+    fn hits_torus_where_rays_from_inside_leave() {
+        let shape = ring();
+        for direction in &[Vector::y(), Vector::x(), Vector::new(-1.0, 1.0, 0.0)] {
+            let inside = ray(Vector::new(1.0, 0.0, 0.0), *direction);
+            let contact = shape.get_contact(&inside).expect("the ray should leave");
+            assert_close(contact.get_distance_from_origin(), 0.25);
+            assert!(contact.is_from_inside());
+            assert_vector_close(
+                &contact.get_facing_normal(),
+                &direction.normalized().times(-1.0),
+            );
+        }
+    }
+
+    #[test]
+    fn grazes_tube_cleanly() {
+        let shape = ring();
+
+        // Touches the tops of both sides of the tube.
+        let tangent = ray(Vector::new(-5.0, 0.25, 0.0), Vector::x());
+        if let Some(contact) = shape.get_contact(&tangent) {
+            assert_finite(&contact);
+            assert!((contact.get_distance_from_origin() - 4.0).abs() < 0.05);
+            assert!((contact.get_position().y - 0.25).abs() < 1e-3);
+        }
+
+        let above = ray(Vector::new(-5.0, 0.2501, 0.0), Vector::x());
+        assert!(shape.get_contact(&above).is_none());
+
+        let below = ray(Vector::new(-5.0, 0.249, 0.0), Vector::x());
+        let contact = shape.get_contact(&below).expect("the ray should hit");
+        assert_finite(&contact);
+        assert!((contact.get_distance_from_origin() - 4.0).abs() < 0.05);
+
+        // Tangent to the outer rim from far away, where the quartic's coefficients are large.
+        let rim = ray(Vector::new(-1.25, 0.0, -1000.0), Vector::z());
+        if let Some(contact) = shape.get_contact(&rim) {
+            assert_finite(&contact);
+            assert!((contact.get_distance_from_origin() - 1000.0).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn maps_torus_into_unit_square() {
+        let shape = ring();
+        for i in 0..16 {
+            for j in 0..8 {
+                let angle = i as f32 * PI / 8.0;
+                let target = Vector::new(angle.cos(), 0.0, angle.sin());
+                let origin = Vector::new(0.0, 2.0, 0.0).plus(&Vector::x().times(j as f32 - 4.0));
+                let incoming = ray(origin, target.minus(&origin));
+                if let Some(contact) = shape.get_contact(&incoming) {
+                    assert_uv_in_range(&contact);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn samples_torus_uniformly() {
+        let shape = ring();
+        let points = sample_grid(&shape);
+        for point in &points {
+            let tube_distance = (point.x.hypot(point.z) - 1.0).hypot(point.y);
+            assert!((tube_distance - 0.25).abs() < 1e-4, "{:?}", point);
+        }
+
+        // The outer half of the tube is the wider one, R·π + 2r against the total 2πR.
+        assert_close(shape.get_surface_area().unwrap(), PI * PI);
+        assert_sampled_share(
+            &points,
+            |point| point.x.hypot(point.z) > 1.0,
+            0.5 + 0.25 / PI,
+        );
+    }
+}
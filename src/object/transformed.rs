@@ -0,0 +1,64 @@
+use crate::primitive::{
+    bounding_box::BoundingBox, contact::RayContact, ray::Ray, transform::Transform, vector::Vector,
+};
+
+use super::Shape;
+
+/*
+ * Places a shape defined in its own object space. Rays are brought into object space, and the
+ * contact found there is carried back out. Distances along the ray are kept since the ray's
+ * direction is transformed without being normalized.
+ */
+pub struct TransformedShape<S: Shape> {
+    shape: S,
+    transform: Transform,
+}
+
+impl<S: Shape> TransformedShape<S> {
+    pub fn new(shape: S, transform: Transform) -> Self {
+        Self { shape, transform }
+    }
+}
+
+impl<S: Shape> Shape for TransformedShape<S> {
+    fn get_contact<'a>(&self, ray: &'a Ray) -> Option<RayContact<'a>> {
+        let object_ray = self.transform.apply_inverse_to_ray(ray);
+        let contact = self.shape.get_contact(&object_ray)?;
+        let tangents = contact.get_tangents();
+
+        let mut placed_contact = RayContact::new(
+            self.transform.apply_to_point(&contact.get_position()),
+            self.transform.apply_to_normal(&contact.get_normal()),
+            ray,
+        )
+        .with_uv(contact.get_uv())
+        .with_tangents(
+            self.transform.apply_to_direction(&tangents[0]),
+            self.transform.apply_to_direction(&tangents[1]),
+        );
+        // Smooth shapes such as meshes with vertex normals shade around their own normal.
+        placed_contact.set_shading_normal(
+            self.transform
+                .apply_to_normal(&contact.get_shading_normal()),
+        );
+        Some(placed_contact)
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        self.transform
+            .apply_to_bounding_box(&self.shape.get_bounding_box())
+    }
+
+    // Uniform sampling survives a transform that keeps angles, a stretched shape is only lit by
+    // the rays that hit it.
+    fn get_surface_area(&self) -> Option<f32> {
+        let scale = self.transform.get_uniform_scale()?;
+        Some(self.shape.get_surface_area()? * scale * scale)
+    }
+
+    fn sample_surface(&self, random: [f32; 2]) -> Option<Vector> {
+        self.transform.get_uniform_scale()?;
+        let point = self.shape.sample_surface(random)?;
+        Some(self.transform.apply_to_point(&point))
+    }
+}
@@ -0,0 +1,229 @@
+use std::{path::PathBuf, time::Duration};
+
+use crate::{
+    raytracing::{
+        adaptive::AdaptiveSampling,
+        export::ImageFormat,
+        runner::RenderBudget,
+        tile::TileOrder,
+        tonemap::{OutputEncoding, ToneMapper},
+    },
+    sampler::SamplerKind,
+};
+
+pub const USAGE: &str = "\
+Usage: rustracing [OPTIONS] [SCENE]
+
+Arguments:
+    [SCENE]               Scene description file (default scenes/cornell.ron)
+
+Options:
+    --width <PIXELS>      Image width (default 600)
+    --height <PIXELS>     Image height (default 500)
+    --headless            Render without opening a window and write the image to disk
+    --samples <COUNT>     Samples per pixel to render in headless mode (default 64)
+    --time <SECONDS>      Render for a fixed time in headless mode instead of a sample count
+    --output <PATH>       Output image path in headless mode (default render.png), the
+                          extension selects the format: png, ppm, exr, hdr or pfm
+    --heatmap <PATH>      Also write a heatmap of the samples each pixel got in headless mode
+    --aovs                Also write depth, normal, albedo, object id and bounce count images in
+                          headless mode, as extra layers for EXR output and as files named
+                          after the output otherwise
+    --tonemap <OPERATOR>  Tone mapping for display and 8-bit output: clamp, reinhard or aces
+                          (default clamp)
+    --exposure <STOPS>    Exposure adjustment applied before tone mapping (default 0)
+    --gamma <VALUE>       Encode with a plain gamma curve instead of sRGB
+    --denoise             Denoise the displayed and saved image, guided by the first hit albedo
+                          and normals
+    --seed <NUMBER>       Seed for the random numbers, the same seed renders the same image
+                          (default 0)
+    --sampler <NAME>      Sample pattern: independent, stratified, halton or sobol
+                          (default sobol)
+    --tile-size <PIXELS>  Width and height of the tiles rendered by each job (default 32)
+    --tile-order <ORDER>  Order the tiles are scheduled in: spiral, hilbert or scanline
+                          (default spiral)
+    --tile-samples <COUNT>
+                          Samples per pixel each tile renders per pass (default 1)
+    --noise-threshold <VALUE>
+                          Stop sampling tiles whose relative error is below VALUE and spend
+                          their samples on the noisier tiles instead
+    --benchmark <COUNT>   Compare BVH and brute force intersection speed with COUNT spheres,
+                          placed from the seed";
+
+const DEFAULT_SCENE_PATH: &str = "scenes/cornell.ron";
+const DEFAULT_SAMPLE_COUNT: u32 = 64;
+const ADAPTIVE_MIN_SAMPLE_COUNT: u32 = 16;
+pub const BENCHMARK_RAY_COUNT: usize = 100000;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+pub struct HeadlessOptions {
+    pub budget: RenderBudget,
+    pub output: PathBuf,
+    pub output_format: ImageFormat,
+    pub heatmap: Option<(PathBuf, ImageFormat)>,
+    pub writes_aovs: bool,
+}
+
+pub struct Options {
+    pub scene_path: PathBuf,
+    pub width: u32,
+    pub height: u32,
+    pub headless: Option<HeadlessOptions>,
+    pub tone_mapper: ToneMapper,
+    pub denoises: bool,
+    pub seed: u64,
+    pub sampler_kind: SamplerKind,
+    pub tile_size: u32,
+    pub tile_order: TileOrder,
+    pub tile_sample_count: u32,
+    pub adaptive_sampling: Option<AdaptiveSampling>,
+    pub benchmark_sphere_count: Option<usize>,
+}
+
+impl Options {
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut scene_path = None;
+        let mut width = 600;
+        let mut height = 500;
+
+        let mut headless = false;
+        let mut budget = None;
+        let mut output = None;
+        let mut heatmap = None;
+        let mut writes_aovs = false;
+        let mut benchmark_sphere_count = None;
+
+        let mut tone_mapper = ToneMapper::default();
+        let mut denoises = false;
+        let mut seed = 0;
+        let mut sampler_kind = SamplerKind::Sobol;
+        let mut tile_size = 32;
+        let mut tile_order = TileOrder::Spiral;
+        let mut tile_sample_count = 1;
+        let mut adaptive_sampling = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--width" => width = parse_value(&arg, args.next())?,
+                "--height" => height = parse_value(&arg, args.next())?,
+                "--headless" => headless = true,
+                "--samples" => {
+                    budget = Some(RenderBudget::SampleCount(parse_value(&arg, args.next())?))
+                }
+                "--time" => {
+                    let seconds: f32 = parse_value(&arg, args.next())?;
+                    let duration = Duration::try_from_secs_f32(seconds)
+                        .ok()
+                        .filter(|_| seconds > 0.0)
+                        .ok_or_else(|| {
+                            format!("time must be a positive number of seconds, got {}", seconds)
+                        })?;
+                    budget = Some(RenderBudget::Duration(duration));
+                }
+                "--output" => {
+                    output = Some(PathBuf::from(parse_value::<String>(&arg, args.next())?))
+                }
+                "--heatmap" => {
+                    heatmap = Some(PathBuf::from(parse_value::<String>(&arg, args.next())?))
+                }
+                "--aovs" => writes_aovs = true,
+                "--tonemap" => tone_mapper.operator = parse_value(&arg, args.next())?,
+                "--exposure" => tone_mapper.exposure = parse_value(&arg, args.next())?,
+                "--gamma" => {
+                    let gamma: f32 = parse_value(&arg, args.next())?;
+                    if gamma <= 0.0 {
+                        return Err(String::from("gamma must be positive"));
+                    }
+                    tone_mapper.encoding = OutputEncoding::Gamma(gamma);
+                }
+                "--denoise" => denoises = true,
+                "--seed" => seed = parse_value(&arg, args.next())?,
+                "--sampler" => sampler_kind = parse_value(&arg, args.next())?,
+                "--tile-size" => tile_size = parse_value(&arg, args.next())?,
+                "--tile-order" => tile_order = parse_value(&arg, args.next())?,
+                "--tile-samples" => tile_sample_count = parse_value(&arg, args.next())?,
+                "--noise-threshold" => {
+                    let noise_threshold: f32 = parse_value(&arg, args.next())?;
+                    if noise_threshold <= 0.0 {
+                        return Err(String::from("noise threshold must be positive"));
+                    }
+                    adaptive_sampling = Some(AdaptiveSampling {
+                        noise_threshold,
+                        min_sample_count: ADAPTIVE_MIN_SAMPLE_COUNT,
+                    });
+                }
+                "--benchmark" => benchmark_sphere_count = Some(parse_value(&arg, args.next())?),
+                _ if arg.starts_with("--") => return Err(format!("unknown argument `{}`", arg)),
+                _ if scene_path.is_none() => scene_path = Some(PathBuf::from(arg)),
+                _ => return Err(format!("unexpected argument `{}`", arg)),
+            }
+        }
+
+        if width == 0 || height == 0 {
+            return Err(String::from("image size must not be zero"));
+        }
+
+        if tile_size == 0 || tile_sample_count == 0 {
+            return Err(String::from(
+                "`--tile-size` and `--tile-samples` must not be zero",
+            ));
+        }
+
+        let headless = if headless {
+            let output = output.unwrap_or_else(|| PathBuf::from("render.png"));
+            let output_format = ImageFormat::from_path(&output)
+                .ok_or_else(|| format!("unsupported output format for `{}`", output.display()))?;
+            let heatmap = match heatmap {
+                Some(path) => {
+                    let format = ImageFormat::from_path(&path).ok_or_else(|| {
+                        format!("unsupported output format for `{}`", path.display())
+                    })?;
+                    Some((path, format))
+                }
+                None => None,
+            };
+
+            Some(HeadlessOptions {
+                budget: budget.unwrap_or(RenderBudget::SampleCount(DEFAULT_SAMPLE_COUNT)),
+                output,
+                output_format,
+                heatmap,
+                writes_aovs,
+            })
+        } else if budget.is_some() || output.is_some() || heatmap.is_some() || writes_aovs {
+            return Err(String::from(
+                "`--samples`, `--time`, `--output`, `--heatmap` and `--aovs` require `--headless`",
+            ));
+        } else if !cfg!(feature = "window") && benchmark_sphere_count.is_none() {
+            return Err(String::from(
+                "this build has no window, render with `--headless`",
+            ));
+        } else {
+            None
+        };
+
+        Ok(Self {
+            scene_path: scene_path.unwrap_or_else(|| PathBuf::from(DEFAULT_SCENE_PATH)),
+            width,
+            height,
+            headless,
+            tone_mapper,
+            denoises,
+            seed,
+            sampler_kind,
+            tile_size,
+            tile_order,
+            tile_sample_count,
+            adaptive_sampling,
+            benchmark_sphere_count,
+        })
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(name: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("missing value for `{}`", name))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{}` for `{}`", value, name))
+}
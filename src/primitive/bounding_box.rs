@@ -0,0 +1,115 @@
+use super::{ray::Ray, vector::Vector};
+
+const FLAT_PADDING: f32 = 0.0001;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[derive(Clone, Copy, Debug)]
+pub struct BoundingBox {
+    pub min: Vector,
+    pub max: Vector,
+}
+
+impl BoundingBox {
+    pub fn new(min: Vector, max: Vector) -> Self {
+        Self { min, max }
+    }
+
+    pub fn empty() -> Self {
+        Self::new(
+            Vector::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            Vector::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+        )
+    }
+
+    pub fn around_point(center: &Vector, half_size: f32) -> Self {
+        let half_diagonal = Vector::new(half_size, half_size, half_size);
+        Self::new(center.minus(&half_diagonal), center.plus(&half_diagonal))
+    }
+
+    pub fn around_points(points: &[Vector]) -> Self {
+        let mut bounding_box = Self::empty();
+        for point in points {
+            bounding_box = bounding_box.including(point);
+        }
+
+        bounding_box.padded()
+    }
+
+    pub fn including(&self, point: &Vector) -> Self {
+        Self::new(self.min.minimum(point), self.max.maximum(point))
+    }
+
+    pub fn union(&self, other: &BoundingBox) -> Self {
+        Self::new(self.min.minimum(&other.min), self.max.maximum(&other.max))
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.min.x.is_finite()
+            && self.min.y.is_finite()
+            && self.min.z.is_finite()
+            && self.max.x.is_finite()
+            && self.max.y.is_finite()
+            && self.max.z.is_finite()
+    }
+
+    pub fn get_center(&self) -> Vector {
+        self.min.plus(&self.max).times(0.5)
+    }
+
+    pub fn get_size(&self) -> Vector {
+        self.max.minus(&self.min)
+    }
+
+    pub fn get_surface_area(&self) -> f32 {
+        let size = self.get_size();
+        if size.x < 0.0 || size.y < 0.0 || size.z < 0.0 {
+            0.0
+        } else {
+            2.0 * (size.x * size.y + size.y * size.z + size.z * size.x)
+        }
+    }
+
+    pub fn get_largest_axis(&self) -> usize {
+        let size = self.get_size();
+        if size.x >= size.y && size.x >= size.z {
+            0
+        } else if size.y >= size.z {
+            1
+        } else {
+            2
+        }
+    }
+
+    pub fn get_entry_multiplier(
+        &self,
+        ray: &Ray,
+        inverse_direction: &Vector,
+        max_multiplier: f32,
+    ) -> Option<f32> {
+        let mut entry = 0.0_f32;
+        let mut exit = max_multiplier;
+        for axis in 0..3 {
+            let origin = ray.origin.get_axis(axis);
+            let inverse = inverse_direction.get_axis(axis);
+
+            let near = (self.min.get_axis(axis) - origin) * inverse;
+            let far = (self.max.get_axis(axis) - origin) * inverse;
+
+            entry = entry.max(near.min(far));
+            exit = exit.min(near.max(far));
+        }
+
+        if entry <= exit {
+            Some(entry)
+        } else {
+            None
+        }
+    }
+
+    // Flat shapes such as planes would otherwise have a zero sized box that rays slip through.
+    fn padded(&self) -> Self {
+        let padding = Vector::new(FLAT_PADDING, FLAT_PADDING, FLAT_PADDING);
+        Self::new(self.min.minus(&padding), self.max.plus(&padding))
+    }
+}
@@ -1,6 +1,8 @@
+#[cfg(feature = "window")]
 use glium::texture::{ClientFormat, PixelValue, ToClientFormat};
+use serde::Deserialize;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default, Deserialize)]
 pub struct Color {
     pub r: f32,
     pub g: f32,
@@ -21,11 +23,16 @@ impl Color {
     }
 
     pub fn plus(self, other: &Self) -> Self {
-        Self::new(
-            (self.r + other.r).min(1.0),
-            (self.g + other.g).min(1.0),
-            (self.b + other.b).min(1.0),
-        )
+        Self::new(self.r + other.r, self.g + other.g, self.b + other.b)
+    }
+
+    // Relative luminance of linear Rec. 709 primaries.
+    pub fn get_luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
+    pub fn get_max_component(&self) -> f32 {
+        self.r.max(self.g).max(self.b)
     }
 
     pub fn filter(self, other: &Self) -> Self {
@@ -33,6 +40,7 @@ impl Color {
     }
 }
 
+#[cfg(feature = "window")]
 impl ToClientFormat for Color {
     fn rgb_format() -> ClientFormat {
         ClientFormat::F32F32F32
@@ -43,6 +51,7 @@ impl ToClientFormat for Color {
     }
 }
 
+#[cfg(feature = "window")]
 unsafe impl PixelValue for Color {
     fn get_format() -> ClientFormat {
         ClientFormat::F32F32F32
@@ -1,36 +1,68 @@
-use std::f32::consts::PI;
-
-use rand::{thread_rng, Rng};
-
 use super::{ray::Ray, vector::Vector};
 
 const WALL_SIZE: f32 = 0.001;
 
 pub struct RayContact<'a> {
     object_id: usize,
+    position: Vector,
     position_inner: Vector,
     position_outer: Vector,
     normal: Vector,
+    // Normal that light is reflected around, the geometric one unless a material perturbs it.
+    shading_normal: Vector,
+    // Directions in which the surface coordinates grow, spanning the surface with the normal.
+    tangents: [Vector; 2],
+    // Surface coordinates, shapes without a parameterization leave them at zero.
+    uv: [f32; 2],
 
     ray: &'a Ray,
     distance_from_origin: f32,
     from_inside: bool,
+
+    surrounding_index_of_refraction: f32,
 }
 
 impl<'a> RayContact<'a> {
     pub fn new(position: Vector, normal: Vector, ray: &'a Ray) -> Self {
         let wall_vector = normal.normalized_to(WALL_SIZE);
         let distance_from_origin = ray.origin.distance_to(&position);
+        let normal = normal.normalized();
 
         Self {
             object_id: 0,
+            position,
             position_inner: position.minus(&wall_vector),
             position_outer: position.plus(&wall_vector),
-            normal: normal.normalized(),
+            normal,
+            shading_normal: normal,
+            tangents: normal.get_perpendiculars(),
+            uv: [0.0, 0.0],
 
             ray,
             distance_from_origin,
             from_inside: ray.direction.dot(&normal) > 0.0,
+
+            surrounding_index_of_refraction: 1.0,
+        }
+    }
+
+    pub fn with_uv(mut self, uv: [f32; 2]) -> Self {
+        self.uv = uv;
+        self
+    }
+
+    // The tangents don't need to be unit length or perpendicular, they're straightened on use.
+    pub fn with_tangents(mut self, tangent: Vector, bitangent: Vector) -> Self {
+        self.tangents = [tangent, bitangent];
+        self
+    }
+
+    // Copy of the contact moved along the surface, for looking up textures next to it.
+    pub fn shifted(&self, offset: &Vector, uv_offset: [f32; 2]) -> Self {
+        Self {
+            position: self.position.plus(offset),
+            uv: [self.uv[0] + uv_offset[0], self.uv[1] + uv_offset[1]],
+            ..*self
         }
     }
 
@@ -42,6 +74,51 @@ impl<'a> RayContact<'a> {
         self.object_id = object_id
     }
 
+    pub fn get_position(&self) -> Vector {
+        self.position
+    }
+
+    // Outward normal of the surface, whichever side the ray came from.
+    pub fn get_normal(&self) -> Vector {
+        self.normal
+    }
+
+    pub fn get_uv(&self) -> [f32; 2] {
+        self.uv
+    }
+
+    /*
+     * Unit tangent and bitangent perpendicular to the shading normal, keeping the direction of the
+     * surface coordinates. Degenerate tangents, as at the poles of a sphere, fall back to any
+     * perpendicular pair.
+     */
+    pub fn get_tangents(&self) -> [Vector; 2] {
+        let normal = &self.shading_normal;
+        let tangent = self.tangents[0].minus(&normal.times(self.tangents[0].dot(normal)));
+        if tangent.len_sqr() < 1e-12 {
+            return normal.get_perpendiculars();
+        }
+
+        let tangent = tangent.normalized();
+        let bitangent = normal.cross(&tangent);
+        if bitangent.dot(&self.tangents[1]) < 0.0 {
+            [tangent, bitangent.times(-1.0)]
+        } else {
+            [tangent, bitangent]
+        }
+    }
+
+    pub fn get_shading_normal(&self) -> Vector {
+        self.shading_normal
+    }
+
+    // Normals bent past the surface are ignored, light scattered around them would go through it.
+    pub fn set_shading_normal(&mut self, normal: Vector) {
+        if normal.dot(&self.normal) > 0.0 {
+            self.shading_normal = normal.normalized();
+        }
+    }
+
     pub fn get_distance_from_origin(&self) -> f32 {
         self.distance_from_origin
     }
@@ -50,62 +127,46 @@ impl<'a> RayContact<'a> {
         self.from_inside
     }
 
-    pub fn get_outer_reflection(&self, roughness: f32) -> Ray {
-        self.get_random_outer_reflection()
+    pub fn get_surrounding_index_of_refraction(&self) -> f32 {
+        self.surrounding_index_of_refraction
     }
 
-    pub fn get_refraction(&self, index_incident: f32, index_refracted: f32) -> Ray {
-        let actual_normal = self.normal.times(if self.from_inside { 1.0 } else { -1.0 });
-        let angle_incident = self.ray.direction.angle_between(&actual_normal);
-
-        let refracted_sin = (index_incident / index_refracted) * angle_incident.sin();
-        if refracted_sin < 1.0 && refracted_sin > -1.0 {
-            let angle_refracted = refracted_sin.asin();
+    pub fn set_surrounding_index_of_refraction(&mut self, index_of_refraction: f32) {
+        self.surrounding_index_of_refraction = index_of_refraction
+    }
 
-            let mut new_direction = Vector::from(&self.ray.direction);
-            if angle_refracted != angle_incident {
-                let angle_diff = angle_refracted - angle_incident;
-                let rotation_axis = actual_normal.cross(&new_direction);
-                new_direction.rotate_around_vector(&rotation_axis, angle_diff);
-            }
+    pub fn is_transmission(&self, direction: &Vector) -> bool {
+        direction.dot(&self.get_facing_geometric_normal()) < 0.0
+    }
 
-            Ray {
-                origin: if self.from_inside {
-                    self.position_outer
-                } else {
-                    self.position_inner
-                },
-                direction: new_direction,
-            }
+    // Shading normal on the side the ray came from, the one materials scatter around.
+    pub fn get_facing_normal(&self) -> Vector {
+        if self.from_inside {
+            self.shading_normal.times(-1.0)
         } else {
-            self.get_mirror_reflection()
+            self.shading_normal
         }
     }
 
-    fn get_mirror_reflection(&self) -> Ray {
-        let onto_normal = self.ray.direction.project_onto(&self.normal);
-        let direction = self.ray.direction.minus(&onto_normal.times(2.0));
-
-        Ray {
-            origin: if self.from_inside {
-                self.position_inner
-            } else {
-                self.position_outer
-            },
-            direction,
+    pub fn get_facing_geometric_normal(&self) -> Vector {
+        if self.from_inside {
+            self.normal.times(-1.0)
+        } else {
+            self.normal
         }
     }
 
-    fn get_random_outer_reflection(&self) -> Ray {
-        let random_perpendicular = self.normal.random_perpendicular();
-        let mut direction = Vector::from(&self.normal);
-        direction.rotate_around_vector(
-            &random_perpendicular,
-            (thread_rng().gen_range(0.0, 1.0) as f32).acos(),
-        );
+    pub fn get_incoming_direction(&self) -> Vector {
+        self.ray.direction.normalized()
+    }
 
+    pub fn spawn_ray(&self, direction: Vector) -> Ray {
         Ray {
-            origin: self.position_outer.clone(),
+            origin: if direction.dot(&self.normal) > 0.0 {
+                self.position_outer
+            } else {
+                self.position_inner
+            },
             direction,
         }
     }
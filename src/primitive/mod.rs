@@ -1,4 +1,6 @@
+pub mod bounding_box;
 pub mod color;
 pub mod contact;
 pub mod ray;
+pub mod transform;
 pub mod vector;
@@ -0,0 +1,315 @@
+use std::f32::consts::PI;
+
+use super::{bounding_box::BoundingBox, ray::Ray, vector::Vector};
+
+// Relative tolerance when checking whether a transform scales every direction the same.
+const UNIFORM_SCALE_TOLERANCE: f32 = 0.0001;
+
+type Matrix = [[f32; 4]; 4];
+
+const IDENTITY: Matrix = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/*-----------------------------------------------------------------------------------------------*/
+
+/*
+ * Affine transform stored as a 4×4 matrix along with its inverse. Transforms are only built from
+ * translations, rotations and non-zero scales, so the inverse is composed alongside the matrix
+ * instead of being solved for.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct Transform {
+    matrix: Matrix,
+    inverse: Matrix,
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Self {
+            matrix: IDENTITY,
+            inverse: IDENTITY,
+        }
+    }
+
+    pub fn translation(offset: &Vector) -> Self {
+        let mut matrix = IDENTITY;
+        let mut inverse = IDENTITY;
+        for axis in 0..3 {
+            matrix[axis][3] = offset.get_axis(axis);
+            inverse[axis][3] = -offset.get_axis(axis);
+        }
+
+        Self { matrix, inverse }
+    }
+
+    // Rotation by `angle` radians around `axis`, counterclockwise looking against the axis.
+    pub fn rotation(axis: &Vector, angle: f32) -> Self {
+        let axis = axis.normalized();
+        let (sin, cos) = angle.sin_cos();
+        let k = [axis.x, axis.y, axis.z];
+        let cross = [[0.0, -k[2], k[1]], [k[2], 0.0, -k[0]], [-k[1], k[0], 0.0]];
+
+        let mut matrix = IDENTITY;
+        let mut inverse = IDENTITY;
+        for row in 0..3 {
+            for column in 0..3 {
+                let identity = if row == column { 1.0 } else { 0.0 };
+                let value =
+                    cos * identity + sin * cross[row][column] + (1.0 - cos) * k[row] * k[column];
+                matrix[row][column] = value;
+                inverse[column][row] = value;
+            }
+        }
+
+        Self { matrix, inverse }
+    }
+
+    // Scales every axis by the matching component of `scale`, none of which may be zero.
+    pub fn scaling(scale: &Vector) -> Self {
+        let mut matrix = IDENTITY;
+        let mut inverse = IDENTITY;
+        for axis in 0..3 {
+            matrix[axis][axis] = scale.get_axis(axis);
+            inverse[axis][axis] = 1.0 / scale.get_axis(axis);
+        }
+
+        Self { matrix, inverse }
+    }
+
+    // Rigid placement that turns the y axis towards `axis` and moves the origin to `center`, for
+    // shapes that are symmetric around their axis.
+    pub fn placing(center: &Vector, axis: &Vector) -> Self {
+        let axis = axis.normalized();
+        let rotation_axis = Vector::y().cross(&axis);
+        let rotation = if rotation_axis.len_sqr() > 0.0 {
+            Self::rotation(&rotation_axis, axis.y.clamp(-1.0, 1.0).acos())
+        } else if axis.y < 0.0 {
+            Self::rotation(&Vector::x(), PI)
+        } else {
+            Self::identity()
+        };
+
+        rotation.then(&Self::translation(center))
+    }
+
+    // This transform followed by `next`.
+    pub fn then(&self, next: &Transform) -> Self {
+        Self {
+            matrix: multiply(&next.matrix, &self.matrix),
+            inverse: multiply(&self.inverse, &next.inverse),
+        }
+    }
+
+    pub fn apply_to_point(&self, point: &Vector) -> Vector {
+        transform_point(&self.matrix, point)
+    }
+
+    pub fn apply_to_direction(&self, direction: &Vector) -> Vector {
+        transform_direction(&self.matrix, direction)
+    }
+
+    // Normals stay perpendicular to the surface through the inverse transpose, the result isn't
+    // normalized.
+    pub fn apply_to_normal(&self, normal: &Vector) -> Vector {
+        let m = &self.inverse;
+        Vector::new(
+            m[0][0] * normal.x + m[1][0] * normal.y + m[2][0] * normal.z,
+            m[0][1] * normal.x + m[1][1] * normal.y + m[2][1] * normal.z,
+            m[0][2] * normal.x + m[1][2] * normal.y + m[2][2] * normal.z,
+        )
+    }
+
+    // The direction isn't normalized, so distances along the ray keep their multipliers.
+    pub fn apply_inverse_to_ray(&self, ray: &Ray) -> Ray {
+        Ray {
+            origin: transform_point(&self.inverse, &ray.origin),
+            direction: transform_direction(&self.inverse, &ray.direction),
+        }
+    }
+
+    // Box around the transformed corners of `bounding_box`, unbounded boxes stay unbounded.
+    pub fn apply_to_bounding_box(&self, bounding_box: &BoundingBox) -> BoundingBox {
+        if !bounding_box.is_finite() {
+            return BoundingBox::new(
+                Vector::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+                Vector::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            );
+        }
+
+        let mut transformed = BoundingBox::empty();
+        let corners = [bounding_box.min, bounding_box.max];
+        for corner in 0..8 {
+            let point = Vector::new(
+                corners[corner & 1].x,
+                corners[(corner >> 1) & 1].y,
+                corners[(corner >> 2) & 1].z,
+            );
+            transformed = transformed.including(&self.apply_to_point(&point));
+        }
+
+        transformed
+    }
+
+    // The scale factor when the transform keeps angles, i.e. a rotation and translation with the
+    // same scale along every axis. Areas grow with its square.
+    pub fn get_uniform_scale(&self) -> Option<f32> {
+        let axes = [
+            self.apply_to_direction(&Vector::x()),
+            self.apply_to_direction(&Vector::y()),
+            self.apply_to_direction(&Vector::z()),
+        ];
+
+        let scale = axes[0].len();
+        let tolerance = UNIFORM_SCALE_TOLERANCE * scale;
+        let is_uniform = axes
+            .iter()
+            .all(|axis| (axis.len() - scale).abs() <= tolerance)
+            && axes[0].dot(&axes[1]).abs() <= tolerance * scale
+            && axes[1].dot(&axes[2]).abs() <= tolerance * scale
+            && axes[2].dot(&axes[0]).abs() <= tolerance * scale;
+
+        if is_uniform {
+            Some(scale)
+        } else {
+            None
+        }
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut result = [[0.0; 4]; 4];
+    for (row, result_row) in result.iter_mut().enumerate() {
+        for (column, value) in result_row.iter_mut().enumerate() {
+            *value = (0..4).map(|i| a[row][i] * b[i][column]).sum();
+        }
+    }
+
+    result
+}
+
+fn transform_point(m: &Matrix, point: &Vector) -> Vector {
+    Vector::new(
+        m[0][0] * point.x + m[0][1] * point.y + m[0][2] * point.z + m[0][3],
+        m[1][0] * point.x + m[1][1] * point.y + m[1][2] * point.z + m[1][3],
+        m[2][0] * point.x + m[2][1] * point.y + m[2][2] * point.z + m[2][3],
+    )
+}
+
+fn transform_direction(m: &Matrix, direction: &Vector) -> Vector {
+    Vector::new(
+        m[0][0] * direction.x + m[0][1] * direction.y + m[0][2] * direction.z,
+        m[1][0] * direction.x + m[1][1] * direction.y + m[1][2] * direction.z,
+        m[2][0] * direction.x + m[2][1] * direction.y + m[2][2] * direction.z,
+    )
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn assert_vector_close(actual: &Vector, expected: &Vector) {
+        assert!(
+            actual.distance_to(expected) <= 1e-4 * expected.len().max(1.0),
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    // Rotation, non-uniform scale and translation, so no part of the matrix is trivial.
+    fn skewed_placement() -> Transform {
+        Transform::rotation(&Vector::new(1.0, 2.0, 3.0), 0.7)
+            .then(&Transform::scaling(&Vector::new(2.0, 0.5, 3.0)))
+            .then(&Transform::translation(&Vector::new(1.0, -2.0, 4.0)))
+    }
+
+    #[test]
+    fn composes_inverse() {
+        let transform = skewed_placement();
+        let product = multiply(&transform.matrix, &transform.inverse);
+        for row in 0..4 {
+            for column in 0..4 {
+                assert!(
+                    (product[row][column] - IDENTITY[row][column]).abs() < 1e-5,
+                    "matrix times inverse is {:?}",
+                    product
+                );
+            }
+        }
+
+        let point = Vector::new(0.3, -1.2, 2.5);
+        let incoming = Ray {
+            origin: transform.apply_to_point(&point),
+            direction: transform.apply_to_direction(&Vector::x()),
+        };
+        let object_ray = transform.apply_inverse_to_ray(&incoming);
+        assert_vector_close(&object_ray.origin, &point);
+        assert_vector_close(&object_ray.direction, &Vector::x());
+    }
+
+    #[test]
+    fn applies_then_in_order() {
+        let rotation = Transform::rotation(&Vector::z(), FRAC_PI_2);
+        let translation = Transform::translation(&Vector::x());
+
+        // Rotating x a quarter turn around z gives y, then the translation moves it along x.
+        assert_vector_close(
+            &rotation.then(&translation).apply_to_point(&Vector::x()),
+            &Vector::new(1.0, 1.0, 0.0),
+        );
+        assert_vector_close(
+            &translation.then(&rotation).apply_to_point(&Vector::x()),
+            &Vector::new(0.0, 2.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn keeps_normals_perpendicular_under_non_uniform_scale() {
+        let transform = skewed_placement();
+        let normal = Vector::new(1.0, 1.0, 0.0);
+        let tangent = Vector::new(1.0, -1.0, 0.5);
+        assert!(normal.dot(&tangent).abs() < 1e-6);
+
+        let transformed_normal = transform.apply_to_normal(&normal).normalized();
+        let transformed_tangent = transform.apply_to_direction(&tangent).normalized();
+        assert!(
+            transformed_normal.dot(&transformed_tangent).abs() < 1e-5,
+            "normal {:?} isn't perpendicular to tangent {:?}",
+            transformed_normal,
+            transformed_tangent
+        );
+
+        // Transforming the normal as a direction would have tilted it off the surface.
+        let naive_normal = transform.apply_to_direction(&normal).normalized();
+        assert!(naive_normal.dot(&transformed_tangent).abs() > 0.1);
+    }
+
+    #[test]
+    fn detects_uniform_scale() {
+        assert_eq!(Transform::identity().get_uniform_scale(), Some(1.0));
+
+        let uniform = Transform::rotation(&Vector::new(1.0, 2.0, 3.0), 0.7)
+            .then(&Transform::scaling(&Vector::new(2.5, 2.5, 2.5)))
+            .then(&Transform::translation(&Vector::new(1.0, -2.0, 4.0)));
+        let scale = uniform
+            .get_uniform_scale()
+            .expect("the scale should be uniform");
+        assert!((scale - 2.5).abs() < 1e-4, "got a scale of {}", scale);
+
+        assert_eq!(skewed_placement().get_uniform_scale(), None);
+        assert_eq!(
+            Transform::scaling(&Vector::new(1.0, 1.0, 1.01)).get_uniform_scale(),
+            None
+        );
+    }
+}
@@ -1,8 +1,6 @@
-use std::f32::consts::PI;
+use serde::Deserialize;
 
-use rand::{thread_rng, Rng};
-
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
 pub struct Vector {
     pub x: f32,
     pub y: f32,
@@ -46,6 +44,22 @@ impl Vector {
         return Self::new(self.x * other.x, self.y * other.y, self.z * other.z);
     }
 
+    pub fn minimum(&self, other: &Vector) -> Self {
+        Self::new(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.z.min(other.z),
+        )
+    }
+
+    pub fn maximum(&self, other: &Vector) -> Self {
+        Self::new(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.z.max(other.z),
+        )
+    }
+
     pub fn dot(&self, other: &Vector) -> f32 {
         return self.x * other.x + self.y * other.y + self.z * other.z;
     }
@@ -87,24 +101,36 @@ impl Vector {
         }
     }
 
-    pub fn random_perpendicular(&self) -> Vector {
-        let arbitrary: [f32; 2] = [1.0, 1.0];
+    // Two unit vectors completing an orthonormal basis with this unit vector (Duff et al. 2017).
+    pub fn get_perpendiculars(&self) -> [Vector; 2] {
+        let sign = 1.0_f32.copysign(self.z);
+        let a = -1.0 / (sign + self.z);
+        let b = self.x * self.y * a;
 
-        let mut perpendicular = if self.x != 0.0 {
-            let perp_x = (-self.y * arbitrary[0] - self.z * arbitrary[1]) / self.x;
-            Vector::new(perp_x, arbitrary[0], arbitrary[1])
-        } else if self.y != 0.0 {
-            let perp_y = (-self.x * arbitrary[0] - self.z * arbitrary[1]) / self.y;
-            Vector::new(arbitrary[0], perp_y, arbitrary[1])
-        } else if self.z != 0.0 {
-            let perp_z = (-self.x * arbitrary[0] - self.y * arbitrary[1]) / self.z;
-            Vector::new(arbitrary[0], arbitrary[1], perp_z)
-        } else {
-            Vector::new(0.0, 0.0, 0.0)
-        };
+        [
+            Vector::new(1.0 + sign * self.x * self.x * a, sign * b, -sign * self.x),
+            Vector::new(b, sign + self.y * self.y * a, -self.y),
+        ]
+    }
 
-        perpendicular.rotate_around_vector(&self, thread_rng().gen_range(0.0, 2.0 * PI));
-        perpendicular
+    pub fn reflected_around(&self, normal: &Vector) -> Self {
+        self.minus(&normal.times(2.0 * self.dot(normal)))
+    }
+
+    // Snell's law for a unit direction crossing into a medium, `normal` faces against the
+    // direction and `eta` is the incident over the refracted index of refraction.
+    pub fn refracted_through(&self, normal: &Vector, eta: f32) -> Option<Self> {
+        let cos_incident = -self.dot(normal);
+        let sin_refracted_sqr = eta * eta * (1.0 - cos_incident * cos_incident).max(0.0);
+        if sin_refracted_sqr >= 1.0 {
+            return None;
+        }
+
+        let cos_refracted = (1.0 - sin_refracted_sqr).sqrt();
+        Some(
+            self.times(eta)
+                .plus(&normal.times(eta * cos_incident - cos_refracted)),
+        )
     }
 
     pub fn normalized(&self) -> Self {
@@ -152,6 +178,14 @@ impl Vector {
         towards.len_sqr()
     }
 
+    pub fn get_axis(&self, axis: usize) -> f32 {
+        match axis {
+            0 => self.x,
+            1 => self.y,
+            _ => self.z,
+        }
+    }
+
     pub fn sum(&self) -> f32 {
         return self.x + self.y + self.z;
     }
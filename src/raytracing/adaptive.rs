@@ -0,0 +1,231 @@
+use crate::primitive::color::Color;
+
+use super::tile::Tile;
+
+// Pixels darker than this are judged by their absolute error, dark noise isn't visible anyway.
+const MIN_ERROR_LUMINANCE: f32 = 0.01;
+// A tile gets at most this many times the regular pass samples when the budget is redistributed.
+const MAX_SAMPLE_MULTIPLIER: u32 = 8;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[derive(Clone, Copy, Debug)]
+pub struct AdaptiveSampling {
+    // Relative standard error of a pixel's luminance below which it counts as converged.
+    pub noise_threshold: f32,
+    // Samples every pixel gets before its variance estimate is trusted.
+    pub min_sample_count: u32,
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Welford's running mean and variance, batches from separate passes are merged with Chan et al.'s
+// parallel update.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RunningVariance {
+    count: u32,
+    mean: f32,
+    squared_deviation_sum: f32,
+}
+
+impl RunningVariance {
+    pub fn add(&mut self, value: f32) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f32;
+        self.squared_deviation_sum += delta * (value - self.mean);
+    }
+
+    pub fn merge(&mut self, other: &RunningVariance) {
+        if other.count == 0 {
+            return;
+        }
+
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.squared_deviation_sum += other.squared_deviation_sum
+            + delta * delta * (self.count as f32 * other.count as f32 / count as f32);
+        self.mean += delta * other.count as f32 / count as f32;
+        self.count = count;
+    }
+
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    // Standard error of the mean relative to the mean itself.
+    pub fn get_relative_error(&self) -> f32 {
+        if self.count < 2 {
+            return f32::INFINITY;
+        }
+
+        let variance = self.squared_deviation_sum / (self.count - 1) as f32;
+        (variance / self.count as f32).sqrt() / self.mean.max(MIN_ERROR_LUMINANCE)
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+/*
+ * Splits the samples a regular pass would trace over the tiles that are still above the noise
+ * threshold, noisier tiles get a larger share. Converged tiles get nothing, so once every tile is
+ * converged all counts are zero.
+ */
+pub fn distribute_samples(
+    tiles: &[Tile],
+    tile_errors: &[f32],
+    noise_threshold: f32,
+    samples_per_pass: u32,
+) -> Vec<u32> {
+    let max_weight = MAX_SAMPLE_MULTIPLIER as f32;
+    let weights: Vec<f32> = tile_errors
+        .iter()
+        .map(|error| {
+            if *error > noise_threshold {
+                (error / noise_threshold).min(max_weight)
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
+    let weighted_pixel_count: f32 = tiles
+        .iter()
+        .zip(&weights)
+        .map(|(tile, weight)| tile.get_pixel_count() as f32 * weight)
+        .sum();
+    if weighted_pixel_count <= 0.0 {
+        return vec![0; tiles.len()];
+    }
+
+    let total_pixel_count: usize = tiles.iter().map(Tile::get_pixel_count).sum();
+    let samples_per_weight =
+        (samples_per_pass as usize * total_pixel_count) as f32 / weighted_pixel_count;
+
+    weights
+        .iter()
+        .map(|weight| {
+            if *weight > 0.0 {
+                ((weight * samples_per_weight).round() as u32)
+                    .max(1)
+                    .min(MAX_SAMPLE_MULTIPLIER * samples_per_pass)
+            } else {
+                0
+            }
+        })
+        .collect()
+}
+
+// Blue for few samples through green and yellow to red for the most, `value` is between 0 and 1.
+pub fn get_heatmap_color(value: f32) -> Color {
+    const STOPS: [(f32, f32, f32); 5] = [
+        (0.0, 0.0, 0.5),
+        (0.0, 0.4, 1.0),
+        (0.0, 0.9, 0.2),
+        (1.0, 0.9, 0.0),
+        (1.0, 0.0, 0.0),
+    ];
+
+    let position = value.clamp(0.0, 1.0) * (STOPS.len() - 1) as f32;
+    let index = (position as usize).min(STOPS.len() - 2);
+    let weight = position - index as f32;
+    let (from, to) = (STOPS[index], STOPS[index + 1]);
+
+    Color::new(
+        from.0 + (to.0 - from.0) * weight,
+        from.1 + (to.1 - from.1) * weight,
+        from.2 + (to.2 - from.2) * weight,
+    )
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tile(x: u32, size: u32) -> Tile {
+        Tile {
+            x,
+            y: 0,
+            width: size,
+            height: size,
+            sample_count: 0,
+        }
+    }
+
+    fn get_budget(tiles: &[Tile], sample_counts: &[u32]) -> usize {
+        tiles
+            .iter()
+            .zip(sample_counts)
+            .map(|(tile, count)| tile.get_pixel_count() * *count as usize)
+            .sum()
+    }
+
+    #[test]
+    fn merges_batches_like_a_single_pass() {
+        let values = [0.3, 1.7, 0.2, 0.9, 2.4, 0.05, 1.1, 0.6, 0.8, 1.9, 0.4];
+        let (first, second) = values.split_at(4);
+
+        let mut merged = RunningVariance::default();
+        first.iter().for_each(|value| merged.add(*value));
+        let mut other = RunningVariance::default();
+        second.iter().for_each(|value| other.add(*value));
+        merged.merge(&other);
+        merged.merge(&RunningVariance::default());
+
+        let count = values.len() as f32;
+        let mean = values.iter().sum::<f32>() / count;
+        let squared_deviation_sum: f32 = values.iter().map(|value| (value - mean).powi(2)).sum();
+
+        assert_eq!(merged.get_count(), values.len() as u32);
+        assert!((merged.mean - mean).abs() < 1e-5, "mean {}", merged.mean);
+        assert!(
+            (merged.squared_deviation_sum - squared_deviation_sum).abs() < 1e-4,
+            "expected a squared deviation sum of {}, got {}",
+            squared_deviation_sum,
+            merged.squared_deviation_sum
+        );
+        let relative_error = (squared_deviation_sum / (count - 1.0) / count).sqrt() / mean;
+        assert!((merged.get_relative_error() - relative_error).abs() < 1e-5);
+    }
+
+    #[test]
+    fn distributes_the_pass_budget_over_noisy_tiles() {
+        let tiles = [tile(0, 4), tile(4, 4), tile(8, 4), tile(12, 4)];
+        // The first tile is converged or capped, the others are two to four times too noisy.
+        let sample_counts = distribute_samples(&tiles, &[0.0, 0.02, 0.04, 0.03], 0.01, 9);
+
+        assert_eq!(sample_counts, vec![0, 8, 16, 12]);
+        assert_eq!(get_budget(&tiles, &sample_counts), 9 * 64);
+    }
+
+    #[test]
+    fn keeps_the_budget_within_rounding() {
+        let tiles = [tile(0, 3), tile(3, 5), tile(8, 2), tile(10, 4)];
+        let tile_errors = [0.013, 0.005, 0.037, 0.021];
+        let sample_counts = distribute_samples(&tiles, &tile_errors, 0.01, 5);
+
+        assert_eq!(sample_counts[1], 0);
+        let pixel_count: usize = tiles.iter().map(Tile::get_pixel_count).sum();
+        let budget = 5 * pixel_count;
+        // Each tile's count is rounded, so a tile can be off by half a sample per pixel.
+        let rounding = pixel_count / 2;
+        let distributed = get_budget(&tiles, &sample_counts);
+        assert!(
+            distributed.max(budget) - distributed.min(budget) <= rounding,
+            "distributed {} samples out of {}",
+            distributed,
+            budget
+        );
+    }
+
+    #[test]
+    fn stops_once_every_tile_is_converged() {
+        let tiles = [tile(0, 4), tile(4, 4)];
+        assert_eq!(
+            distribute_samples(&tiles, &[0.0, 0.01], 0.01, 4),
+            vec![0, 0]
+        );
+    }
+}
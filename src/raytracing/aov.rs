@@ -0,0 +1,71 @@
+use std::fmt;
+
+use crate::primitive::color::Color;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Arbitrary output variables, what the camera rays' first hits saw next to the rendered image.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Aov {
+    Depth,
+    Normal,
+    Albedo,
+    ObjectId,
+    BounceCount,
+}
+
+impl Aov {
+    pub const ALL: [Aov; 5] = [
+        Aov::Depth,
+        Aov::Normal,
+        Aov::Albedo,
+        Aov::ObjectId,
+        Aov::BounceCount,
+    ];
+
+    // Steps through the rendered image, `None`, and then every output variable.
+    #[cfg(feature = "window")]
+    pub fn next(view: Option<Aov>) -> Option<Aov> {
+        match view {
+            None => Some(Aov::ALL[0]),
+            Some(aov) => {
+                let index = Aov::ALL.iter().position(|other| *other == aov).unwrap_or(0);
+                Aov::ALL.get(index + 1).copied()
+            }
+        }
+    }
+}
+
+impl fmt::Display for Aov {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Aov::Depth => write!(f, "depth"),
+            Aov::Normal => write!(f, "normal"),
+            Aov::Albedo => write!(f, "albedo"),
+            Aov::ObjectId => write!(f, "object_id"),
+            Aov::BounceCount => write!(f, "bounce_count"),
+        }
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// A color that stays the same for an object between frames, neighbouring ids get unrelated ones.
+pub fn get_object_color(object_id: Option<usize>) -> Color {
+    match object_id {
+        Some(object_id) => {
+            let hash = (object_id as u32 + 1).wrapping_mul(0x9e3779b9);
+            Color::new(
+                (hash >> 24) as f32 / 255.0,
+                ((hash >> 16) & 0xff) as f32 / 255.0,
+                ((hash >> 8) & 0xff) as f32 / 255.0,
+            )
+        }
+        None => Color::zero(),
+    }
+}
+
+// Number written for an object in the EXR layer, pixels that hit nothing get `u32::MAX`.
+pub fn get_object_id_value(object_id: Option<usize>) -> u32 {
+    object_id.map_or(u32::MAX, |object_id| object_id as u32)
+}
@@ -0,0 +1,98 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    material::emissive::EmissiveMaterial,
+    object::{sphere::SphereShape, PhysicalObject, ShapeProperties},
+    primitive::{color::Color, ray::Ray, vector::Vector},
+    sampler::{independent::IndependentSampler, Sampler},
+};
+
+use super::scene::RaytracingScene;
+
+const SCENE_HALF_SIZE: f32 = 10.0;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// The spheres and rays come from `seed`, so runs with the same seed time the same work.
+pub fn run_intersection_benchmark(sphere_count: usize, ray_count: usize, seed: u64) {
+    let mut sampler = IndependentSampler::new(seed, 0, 0);
+
+    let properties = ShapeProperties {
+        material: Arc::new(EmissiveMaterial::new(Color::new(1.0, 1.0, 1.0), 1.0)),
+        medium: None,
+    };
+
+    let mut scene = RaytracingScene::new(1, 1);
+    for _ in 0..sphere_count {
+        scene.add_object(PhysicalObject::new(
+            properties.clone(),
+            SphereShape::new(
+                random_position(&mut sampler),
+                0.05 + 0.25 * sampler.get_random_number(),
+            ),
+        ));
+    }
+
+    let build_start = Instant::now();
+    scene.update_bvh();
+    let build_duration = build_start.elapsed();
+
+    let rays: Vec<Ray> = (0..ray_count)
+        .map(|_| Ray {
+            origin: random_position(&mut sampler),
+            direction: random_position(&mut sampler).normalized(),
+        })
+        .collect();
+
+    let (linear_duration, linear_hits) = time_intersections(&rays, |ray| {
+        scene.find_closest_contact_linear(ray).is_some()
+    });
+    let (bvh_duration, bvh_hits) =
+        time_intersections(&rays, |ray| scene.find_closest_contact(ray).is_some());
+
+    println!(
+        "{} spheres, {} rays, seed {}",
+        sphere_count, ray_count, seed
+    );
+    println!(
+        "BVH build:   {:.2} ms",
+        build_duration.as_secs_f64() * 1000.0
+    );
+    println!(
+        "Brute force: {:.0} rays/s ({} hits)",
+        get_rays_per_second(ray_count, linear_duration),
+        linear_hits
+    );
+    println!(
+        "BVH:         {:.0} rays/s ({} hits)",
+        get_rays_per_second(ray_count, bvh_duration),
+        bvh_hits
+    );
+    println!(
+        "Speedup:     {:.1}x",
+        linear_duration.as_secs_f64() / bvh_duration.as_secs_f64()
+    );
+}
+
+fn random_position(sampler: &mut dyn Sampler) -> Vector {
+    let mut random_coordinate = || SCENE_HALF_SIZE * (2.0 * sampler.get_random_number() - 1.0);
+    Vector::new(
+        random_coordinate(),
+        random_coordinate(),
+        random_coordinate(),
+    )
+}
+
+fn time_intersections<F: Fn(&Ray) -> bool>(rays: &[Ray], intersect: F) -> (Duration, usize) {
+    let start = Instant::now();
+    let hit_count = rays.iter().filter(|ray| intersect(ray)).count();
+
+    (start.elapsed(), hit_count)
+}
+
+fn get_rays_per_second(ray_count: usize, duration: Duration) -> f64 {
+    ray_count as f64 / duration.as_secs_f64()
+}
@@ -0,0 +1,110 @@
+// Checks that the low discrepancy samplers converge faster than independent sampling on a small
+// scene, by their error against a reference render at the same sample count.
+
+use std::sync::Arc;
+
+use crate::{
+    camera::OrbitalCamera,
+    material::lambertian::LambertianMaterial,
+    object::{infinite_plane::InfinitePlaneShape, sphere::SphereShape, ShapeProperties},
+    primitive::{color::Color, vector::Vector},
+    sampler::SamplerKind,
+};
+
+use super::{
+    environment::{Environment, GradientBackground},
+    runner::{RaytracingRunner, RenderBudget},
+};
+
+const IMAGE_SIZE: u32 = 12;
+const SAMPLE_COUNT: u32 = 16;
+// The reference gets this many times the compared sample count, with its own seed and sampler so
+// its remaining noise isn't correlated with the images it is compared against.
+const REFERENCE_SAMPLE_MULTIPLIER: u32 = 64;
+const REFERENCE_SEED: u64 = 0x5eed;
+const SEED: u64 = 1;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// A diffuse sphere on a floor under a sky gradient, lit only by the environment.
+fn build_runner(sampler_kind: SamplerKind, seed: u64) -> RaytracingRunner {
+    let camera = OrbitalCamera::new(IMAGE_SIZE, IMAGE_SIZE, Vector::new(0.0, 0.0, 0.0), 4.0, 0.0);
+    let mut runner = RaytracingRunner::new(IMAGE_SIZE, IMAGE_SIZE, camera, 1, 2);
+
+    let properties = ShapeProperties {
+        material: Arc::new(LambertianMaterial::new(Arc::new(Color::new(0.7, 0.5, 0.3)))),
+        medium: None,
+    };
+    runner.add_object(
+        properties.clone(),
+        SphereShape::new(Vector::new(0.0, 0.0, 0.0), 1.0),
+    );
+    runner.add_object(
+        properties,
+        InfinitePlaneShape::new(Vector::new(0.0, -1.0, 0.0), Vector::y()),
+    );
+    runner.set_environment(Some(Environment::new(
+        Box::new(GradientBackground::new(
+            Color::new(0.2, 0.2, 0.2),
+            Color::new(0.6, 0.8, 1.0),
+        )),
+        0.0,
+        1.0,
+    )));
+
+    runner.set_sampler_kind(sampler_kind);
+    runner.set_seed(seed);
+    runner.set_adaptive_sampling(None);
+    runner
+}
+
+fn render(sampler_kind: SamplerKind, seed: u64, sample_count: u32) -> Vec<Color> {
+    let mut runner = build_runner(sampler_kind, seed);
+    runner.render(RenderBudget::SampleCount(sample_count));
+    runner.get_image_data().to_vec()
+}
+
+fn get_root_mean_square_error(image: &[Color], reference: &[Color]) -> f32 {
+    let squared_error_sum: f64 = image
+        .iter()
+        .zip(reference)
+        .map(|(color, reference_color)| {
+            let r = (color.r - reference_color.r) as f64;
+            let g = (color.g - reference_color.g) as f64;
+            let b = (color.b - reference_color.b) as f64;
+            r * r + g * g + b * b
+        })
+        .sum();
+
+    (squared_error_sum / (3 * image.len()) as f64).sqrt() as f32
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[test]
+fn low_discrepancy_samplers_beat_independent_sampling() {
+    let reference = render(
+        SamplerKind::Independent,
+        REFERENCE_SEED,
+        SAMPLE_COUNT * REFERENCE_SAMPLE_MULTIPLIER,
+    );
+    let get_error = |sampler_kind| {
+        get_root_mean_square_error(&render(sampler_kind, SEED, SAMPLE_COUNT), &reference)
+    };
+
+    let independent_error = get_error(SamplerKind::Independent);
+    for sampler_kind in &[
+        SamplerKind::Stratified,
+        SamplerKind::Halton,
+        SamplerKind::Sobol,
+    ] {
+        let error = get_error(*sampler_kind);
+        assert!(
+            error < independent_error,
+            "{} has an RMSE of {} against {} for independent sampling",
+            sampler_kind,
+            error,
+            independent_error
+        );
+    }
+}
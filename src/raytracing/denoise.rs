@@ -0,0 +1,143 @@
+use crate::primitive::{color::Color, vector::Vector};
+
+// Each pass doubles the gap between the taps, five passes cover a 125 pixel wide footprint.
+const ITERATION_COUNT: u32 = 5;
+// B3 spline weights for the taps at offsets 0, ±1 and ±2.
+const KERNEL: [f32; 3] = [3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+
+// Edge-stopping falloffs. The color one halves every pass as the image gets smoother.
+const COLOR_SIGMA: f32 = 0.5;
+const NORMAL_SIGMA: f32 = 0.3;
+const ALBEDO_SIGMA: f32 = 0.1;
+
+// Albedos are kept above this when dividing them out, so black surfaces don't blow up.
+const MIN_ALBEDO: f32 = 0.01;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+/*
+ * Edge-avoiding à-trous wavelet filter, after Dammertz et al. (2010). The albedo is divided out
+ * first so only the lighting is blurred and texture detail survives, taps are weighted down where
+ * their albedo, normal or lighting differs from the center pixel's.
+ */
+pub fn denoise(
+    width: u32,
+    height: u32,
+    colors: &[Color],
+    albedos: &[Color],
+    normals: &[Vector],
+) -> Vec<Color> {
+    let demodulators: Vec<Color> = albedos
+        .iter()
+        .map(|albedo| {
+            Color::new(
+                albedo.r.max(MIN_ALBEDO),
+                albedo.g.max(MIN_ALBEDO),
+                albedo.b.max(MIN_ALBEDO),
+            )
+        })
+        .collect();
+
+    let mut lighting: Vec<Color> = colors
+        .iter()
+        .zip(&demodulators)
+        .map(|(color, demodulator)| {
+            Color::new(
+                color.r / demodulator.r,
+                color.g / demodulator.g,
+                color.b / demodulator.b,
+            )
+        })
+        .collect();
+
+    for iteration in 0..ITERATION_COUNT {
+        let step = 1 << iteration;
+        let color_sigma = COLOR_SIGMA / (1 << iteration) as f32;
+        lighting = filter_step(
+            width,
+            height,
+            &lighting,
+            albedos,
+            normals,
+            step,
+            color_sigma,
+        );
+    }
+
+    lighting
+        .iter()
+        .zip(&demodulators)
+        .map(|(color, demodulator)| color.filter(demodulator))
+        .collect()
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+fn filter_step(
+    width: u32,
+    height: u32,
+    lighting: &[Color],
+    albedos: &[Color],
+    normals: &[Vector],
+    step: i32,
+    color_sigma: f32,
+) -> Vec<Color> {
+    // Lighting is compared after compressing it to [0, 1), fireflies would stop every tap otherwise.
+    let compressed: Vec<Color> = lighting.iter().map(get_compressed).collect();
+
+    let (width, height) = (width as i32, height as i32);
+    let mut filtered = Vec::with_capacity(lighting.len());
+    for y in 0..height {
+        for x in 0..width {
+            let center = (y * width + x) as usize;
+
+            let mut color_sum = Color::zero();
+            let mut weight_sum = 0.0;
+            for offset_y in -2..=2 {
+                let tap_y = y + offset_y * step;
+                if tap_y < 0 || tap_y >= height {
+                    continue;
+                }
+
+                for offset_x in -2..=2 {
+                    let tap_x = x + offset_x * step;
+                    if tap_x < 0 || tap_x >= width {
+                        continue;
+                    }
+
+                    let tap = (tap_y * width + tap_x) as usize;
+                    let exponent = get_squared_distance(&compressed[center], &compressed[tap])
+                        / (color_sigma * color_sigma)
+                        + normals[center].distance_to_sqr(&normals[tap])
+                            / (NORMAL_SIGMA * NORMAL_SIGMA)
+                        + get_squared_distance(&albedos[center], &albedos[tap])
+                            / (ALBEDO_SIGMA * ALBEDO_SIGMA);
+                    let weight = KERNEL[offset_x.unsigned_abs() as usize]
+                        * KERNEL[offset_y.unsigned_abs() as usize]
+                        * (-exponent).exp();
+
+                    color_sum = color_sum.plus(&lighting[tap].times(weight));
+                    weight_sum += weight;
+                }
+            }
+
+            // The center tap always has a positive weight.
+            filtered.push(color_sum.times(1.0 / weight_sum));
+        }
+    }
+
+    filtered
+}
+
+fn get_compressed(color: &Color) -> Color {
+    Color::new(
+        color.r / (1.0 + color.r),
+        color.g / (1.0 + color.g),
+        color.b / (1.0 + color.b),
+    )
+}
+
+fn get_squared_distance(a: &Color, b: &Color) -> f32 {
+    let (r, g, b) = (a.r - b.r, a.g - b.g, a.b - b.b);
+    r * r + g * g + b * b
+}
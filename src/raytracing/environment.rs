@@ -0,0 +1,509 @@
+use std::{
+    f32::consts::PI,
+    fs::File,
+    io::{self, BufReader},
+    path::Path,
+};
+
+use image::codecs::hdr::HdrDecoder;
+
+use crate::{
+    material::sampling::{get_cosine_hemisphere_pdf, sample_cosine_hemisphere, to_world},
+    primitive::{color::Color, vector::Vector},
+};
+
+use super::export::ImageFormat;
+
+// Chance of the sky aiming a light sample at the sun rather than anywhere above the horizon.
+const SUN_SAMPLING_PROBABILITY: f32 = 0.5;
+// Illuminance of the sun before the atmosphere, in the kilolux the sky model works in.
+const SUN_ILLUMINANCE: f32 = 128.0;
+// Scale from the sky model's luminance to the radiance of emissive materials, which puts a clear
+// sky at about the brightness of a lamp with an intensity of 1.
+const SKY_RADIANCE_SCALE: f32 = 0.05;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// What rays that leave the scene see, for directions of unit length.
+pub trait Background: Sync + Send {
+    fn get_radiance(&self, direction: &Vector) -> Color;
+
+    // Backgrounds with bright regions pick directions towards them as emitters pick points, along
+    // with the solid angle density of the pick. The others are only found by escaping rays.
+    fn is_sampled(&self) -> bool {
+        false
+    }
+
+    fn sample_direction(&self, _random: [f32; 2]) -> Option<(Vector, f32)> {
+        None
+    }
+
+    fn get_pdf(&self, _direction: &Vector) -> f32 {
+        0.0
+    }
+}
+
+/*
+ * Background of a scene, turned `rotation_angle` around the y axis and scaled by `intensity`.
+ * Rotations keep solid angles, so the densities of the background hold for the turned one.
+ */
+pub struct Environment {
+    background: Box<dyn Background>,
+    rotation_angle: f32,
+    intensity: f32,
+}
+
+impl Environment {
+    pub fn new(background: Box<dyn Background>, rotation_angle: f32, intensity: f32) -> Self {
+        Self {
+            background,
+            rotation_angle,
+            intensity,
+        }
+    }
+
+    pub fn get_radiance(&self, direction: &Vector) -> Color {
+        self.background
+            .get_radiance(&self.to_background(direction))
+            .times(self.intensity)
+    }
+
+    pub fn is_sampled(&self) -> bool {
+        self.background.is_sampled()
+    }
+
+    pub fn sample_direction(&self, random: [f32; 2]) -> Option<(Vector, f32)> {
+        let (mut direction, pdf) = self.background.sample_direction(random)?;
+        direction.rotate_around_vector(&Vector::y(), self.rotation_angle);
+        Some((direction, pdf))
+    }
+
+    pub fn get_pdf(&self, direction: &Vector) -> f32 {
+        self.background.get_pdf(&self.to_background(direction))
+    }
+
+    fn to_background(&self, direction: &Vector) -> Vector {
+        let mut direction = direction.normalized();
+        direction.rotate_around_vector(&Vector::y(), -self.rotation_angle);
+        direction
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+pub struct ConstantBackground {
+    color: Color,
+}
+
+impl ConstantBackground {
+    pub fn new(color: Color) -> Self {
+        Self { color }
+    }
+}
+
+impl Background for ConstantBackground {
+    fn get_radiance(&self, _direction: &Vector) -> Color {
+        self.color
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Blend from `bottom` straight down to `top` straight up.
+pub struct GradientBackground {
+    bottom: Color,
+    top: Color,
+}
+
+impl GradientBackground {
+    pub fn new(bottom: Color, top: Color) -> Self {
+        Self { bottom, top }
+    }
+}
+
+impl Background for GradientBackground {
+    fn get_radiance(&self, direction: &Vector) -> Color {
+        let weight = (0.5 + 0.5 * direction.y).clamp(0.0, 1.0);
+        self.bottom
+            .times(1.0 - weight)
+            .plus(&self.top.times(weight))
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+/*
+ * Clear sky from Preetham et al. 1999, with the sun as a disk dimmed and reddened by the air it
+ * shines through. `turbidity` is the haze in the air, from 2 for a clear day to about 10 for a
+ * hazy one. The ground isn't modeled, directions below the horizon see the horizon.
+ */
+pub struct SkyBackground {
+    sun_direction: Vector,
+    sun_cos_radius: f32,
+    sun_radiance: Color,
+
+    // Perez distribution coefficients of the luminance and the two chromaticity coordinates.
+    coefficients: [[f32; 5]; 3],
+    // Luminance and chromaticity at the zenith, divided by the distribution there.
+    zenith: [f32; 3],
+}
+
+impl SkyBackground {
+    pub fn new(sun_direction: Vector, turbidity: f32, sun_radius: f32) -> Self {
+        let sun_direction = sun_direction.normalized();
+        let t = turbidity;
+        let coefficients = [
+            [
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703,
+            ],
+            [
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452,
+            ],
+            [
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529,
+            ],
+        ];
+
+        // The sun stops at the horizon, the fits aren't meant for it to set.
+        let sun_theta = sun_direction.y.clamp(0.0, 1.0).acos().min(PI / 2.0 - 0.01);
+        let (theta, theta_sqr, theta_cube) = (sun_theta, sun_theta * sun_theta, sun_theta.powi(3));
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * sun_theta);
+        let zenith_luminance = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+        let zenith_x = t * t * (0.00166 * theta_cube - 0.00375 * theta_sqr + 0.00209 * theta)
+            + t * (-0.02903 * theta_cube + 0.06377 * theta_sqr - 0.03202 * theta + 0.00394)
+            + (0.11693 * theta_cube - 0.21196 * theta_sqr + 0.06052 * theta + 0.25886);
+        let zenith_y = t * t * (0.00275 * theta_cube - 0.00610 * theta_sqr + 0.00317 * theta)
+            + t * (-0.04214 * theta_cube + 0.08970 * theta_sqr - 0.04153 * theta + 0.00516)
+            + (0.15346 * theta_cube - 0.26756 * theta_sqr + 0.06670 * theta + 0.26688);
+
+        let mut zenith = [zenith_luminance, zenith_x, zenith_y];
+        for (value, coefficients) in zenith.iter_mut().zip(&coefficients) {
+            *value /= get_perez(coefficients, 1.0, sun_theta.cos());
+        }
+
+        let sun_cos_radius = sun_radius.cos();
+        let sun_solid_angle = 2.0 * PI * (1.0 - sun_cos_radius);
+        let sun_radiance = get_sun_transmittance(sun_direction.y, turbidity)
+            .times(SUN_ILLUMINANCE * SKY_RADIANCE_SCALE / sun_solid_angle);
+
+        Self {
+            sun_direction,
+            sun_cos_radius,
+            sun_radiance,
+
+            coefficients,
+            zenith,
+        }
+    }
+
+    fn get_sky_radiance(&self, direction: &Vector) -> Color {
+        let cos_theta = direction.y.max(0.001);
+        let cos_gamma = direction.dot(&self.sun_direction).clamp(-1.0, 1.0);
+
+        let mut values = [0.0; 3];
+        for (index, value) in values.iter_mut().enumerate() {
+            *value =
+                self.zenith[index] * get_perez(&self.coefficients[index], cos_theta, cos_gamma);
+        }
+
+        let [luminance, x, y] = values;
+        if y <= 0.0 {
+            return Color::zero();
+        }
+
+        // From xyY to XYZ, then to linear sRGB.
+        let big_x = x / y * luminance;
+        let big_z = (1.0 - x - y) / y * luminance;
+        Color::new(
+            3.2406 * big_x - 1.5372 * luminance - 0.4986 * big_z,
+            -0.9689 * big_x + 1.8758 * luminance + 0.0415 * big_z,
+            0.0557 * big_x - 0.2040 * luminance + 1.0570 * big_z,
+        )
+        .times(SKY_RADIANCE_SCALE)
+    }
+
+    fn get_sun_pdf(&self) -> f32 {
+        1.0 / (2.0 * PI * (1.0 - self.sun_cos_radius))
+    }
+}
+
+impl Background for SkyBackground {
+    fn get_radiance(&self, direction: &Vector) -> Color {
+        let sky = self.get_sky_radiance(direction);
+        if direction.dot(&self.sun_direction) >= self.sun_cos_radius && direction.y > 0.0 {
+            sky.plus(&self.sun_radiance)
+        } else {
+            sky
+        }
+    }
+
+    fn is_sampled(&self) -> bool {
+        true
+    }
+
+    fn sample_direction(&self, random: [f32; 2]) -> Option<(Vector, f32)> {
+        let direction = if random[0] < SUN_SAMPLING_PROBABILITY {
+            // Uniformly within the cone of the sun's disk.
+            let cos_theta =
+                1.0 - random[0] / SUN_SAMPLING_PROBABILITY * (1.0 - self.sun_cos_radius);
+            let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+            let phi = 2.0 * PI * random[1];
+            let local = Vector::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta);
+            to_world(&self.sun_direction, &local)
+        } else {
+            let rescaled =
+                (random[0] - SUN_SAMPLING_PROBABILITY) / (1.0 - SUN_SAMPLING_PROBABILITY);
+            sample_cosine_hemisphere(&Vector::y(), [rescaled, random[1]])
+        };
+
+        Some((direction, self.get_pdf(&direction)))
+    }
+
+    fn get_pdf(&self, direction: &Vector) -> f32 {
+        let sun = if direction.dot(&self.sun_direction) >= self.sun_cos_radius {
+            self.get_sun_pdf()
+        } else {
+            0.0
+        };
+
+        SUN_SAMPLING_PROBABILITY * sun
+            + (1.0 - SUN_SAMPLING_PROBABILITY) * get_cosine_hemisphere_pdf(direction.y)
+    }
+}
+
+// Perez et al. sky distribution, relative brightness at `theta` from the zenith and `gamma` from
+// the sun.
+fn get_perez(coefficients: &[f32; 5], cos_theta: f32, cos_gamma: f32) -> f32 {
+    let [a, b, c, d, e] = *coefficients;
+    let gamma = cos_gamma.acos();
+    (1.0 + a * (b / cos_theta).exp()) * (1.0 + c * (d * gamma).exp() + e * cos_gamma * cos_gamma)
+}
+
+/*
+ * Share of the sunlight that makes it through the air, lost to scattering by molecules, which
+ * takes more of the blue, and by haze. The air mass is how many times thicker the air is along
+ * the sun's rays than straight up (Kasten and Young 1989).
+ */
+fn get_sun_transmittance(sun_height: f32, turbidity: f32) -> Color {
+    let elevation = sun_height.clamp(0.0, 1.0).asin().to_degrees();
+    let air_mass = 1.0 / (sun_height.max(0.0) + 0.50572 * (elevation + 6.07995).powf(-1.6364));
+    let haze = 0.04608 * turbidity - 0.04586;
+
+    // Red, green and blue wavelengths in micrometers.
+    let [r, g, b] = [0.68_f32, 0.55, 0.44].map(|wavelength| {
+        let rayleigh = 0.008735 * wavelength.powf(-4.08);
+        let aerosol = haze * wavelength.powf(-1.3);
+        (-(rayleigh + aerosol) * air_mass).exp()
+    });
+
+    Color::new(r, g, b)
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Piecewise constant distribution over a row of values, picked proportionally to them.
+struct Distribution {
+    // Cumulative share of the values before each one, the last entry is 1.
+    cdf: Vec<f32>,
+}
+
+impl Distribution {
+    // Rows of nothing but zeros are picked uniformly instead.
+    fn new(weights: &[f32]) -> Self {
+        let total: f32 = weights.iter().sum();
+        let mut cdf = Vec::with_capacity(weights.len() + 1);
+        cdf.push(0.0);
+
+        let mut sum = 0.0;
+        for (index, weight) in weights.iter().enumerate() {
+            sum += weight;
+            cdf.push(if total > 0.0 {
+                sum / total
+            } else {
+                (index + 1) as f32 / weights.len() as f32
+            });
+        }
+
+        Self { cdf }
+    }
+
+    // Index of the picked value and where the random number fell within its share.
+    fn sample(&self, random: f32) -> (usize, f32) {
+        let index =
+            (self.cdf.partition_point(|&value| value <= random) - 1).min(self.cdf.len() - 2);
+        let share = self.get_probability(index);
+        let offset = if share > 0.0 {
+            ((random - self.cdf[index]) / share).clamp(0.0, 1.0)
+        } else {
+            0.5
+        };
+
+        (index, offset)
+    }
+
+    fn get_probability(&self, index: usize) -> f32 {
+        self.cdf[index + 1] - self.cdf[index]
+    }
+}
+
+/*
+ * Panorama in equirectangular projection: columns go around the y axis, rows from straight up at
+ * the top to straight down at the bottom. Directions are sampled proportionally to the brightness
+ * of the pixels, picking a row first and then a pixel within it, with rows near the poles made
+ * less likely since their pixels cover less of the sphere.
+ */
+pub struct MapBackground {
+    width: usize,
+    height: usize,
+    // Linear colors, top row first.
+    data: Vec<Color>,
+
+    rows: Distribution,
+    columns: Vec<Distribution>,
+}
+
+impl MapBackground {
+    // Radiance .hdr and OpenEXR files, which keep the brightness of the sun and lamps.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let (width, height, data) = match ImageFormat::from_path(path) {
+            Some(ImageFormat::Hdr) => read_hdr(path)?,
+            Some(ImageFormat::Exr) => read_exr(path)?,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "environment maps must be .hdr or .exr files",
+                ))
+            }
+        };
+
+        if width == 0 || height == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the environment map is empty",
+            ));
+        }
+
+        Ok(Self::new(width, height, data))
+    }
+
+    fn new(width: usize, height: usize, data: Vec<Color>) -> Self {
+        let mut row_weights = Vec::with_capacity(height);
+        let mut columns = Vec::with_capacity(height);
+        for (row, pixels) in data.chunks(width).enumerate() {
+            let sin_theta = (PI * (row as f32 + 0.5) / height as f32).sin();
+            let weights: Vec<f32> = pixels
+                .iter()
+                .map(|color| color.get_luminance().max(0.0) * sin_theta)
+                .collect();
+
+            row_weights.push(weights.iter().sum());
+            columns.push(Distribution::new(&weights));
+        }
+
+        Self {
+            width,
+            height,
+            data,
+
+            rows: Distribution::new(&row_weights),
+            columns,
+        }
+    }
+
+    fn get_pixel(&self, direction: &Vector) -> (usize, usize) {
+        let u = (direction.z.atan2(direction.x) / (2.0 * PI)).rem_euclid(1.0);
+        let v = direction.y.clamp(-1.0, 1.0).acos() / PI;
+
+        (
+            ((u * self.width as f32) as usize).min(self.width - 1),
+            ((v * self.height as f32) as usize).min(self.height - 1),
+        )
+    }
+}
+
+impl Background for MapBackground {
+    fn get_radiance(&self, direction: &Vector) -> Color {
+        let (x, y) = self.get_pixel(direction);
+        self.data[y * self.width + x]
+    }
+
+    fn is_sampled(&self) -> bool {
+        true
+    }
+
+    fn sample_direction(&self, random: [f32; 2]) -> Option<(Vector, f32)> {
+        let (y, offset_y) = self.rows.sample(random[1]);
+        let (x, offset_x) = self.columns[y].sample(random[0]);
+
+        let phi = 2.0 * PI * (x as f32 + offset_x) / self.width as f32;
+        let theta = PI * (y as f32 + offset_y) / self.height as f32;
+        let direction = Vector::new(
+            theta.sin() * phi.cos(),
+            theta.cos(),
+            theta.sin() * phi.sin(),
+        );
+
+        Some((direction, self.get_pdf(&direction)))
+    }
+
+    fn get_pdf(&self, direction: &Vector) -> f32 {
+        let sin_theta = (1.0 - direction.y * direction.y).max(0.0).sqrt();
+        if sin_theta == 0.0 {
+            return 0.0;
+        }
+
+        // The picked pixel's share spread over its patch of the panorama, which covers
+        // 2π² sin θ of solid angle per unit of the image's width and height.
+        let (x, y) = self.get_pixel(direction);
+        let probability = self.rows.get_probability(y) * self.columns[y].get_probability(x);
+        probability * (self.width * self.height) as f32 / (2.0 * PI * PI * sin_theta)
+    }
+}
+
+fn read_hdr(path: &Path) -> io::Result<(usize, usize, Vec<Color>)> {
+    let to_io_error = |error| io::Error::new(io::ErrorKind::InvalidData, error);
+
+    let decoder = HdrDecoder::new(BufReader::new(File::open(path)?)).map_err(to_io_error)?;
+    let metadata = decoder.metadata();
+    let pixels = decoder.read_image_hdr().map_err(to_io_error)?;
+
+    Ok((
+        metadata.width as usize,
+        metadata.height as usize,
+        pixels
+            .iter()
+            .map(|pixel| Color::new(pixel[0], pixel[1], pixel[2]))
+            .collect(),
+    ))
+}
+
+fn read_exr(path: &Path) -> io::Result<(usize, usize, Vec<Color>)> {
+    let image = exr::prelude::read_first_rgba_layer_from_file(
+        path,
+        |resolution, _| {
+            (
+                resolution.width(),
+                vec![Color::zero(); resolution.width() * resolution.height()],
+            )
+        },
+        |(width, pixels), position, (r, g, b, _): (f32, f32, f32, f32)| {
+            pixels[position.y() * *width + position.x()] = Color::new(r, g, b);
+        },
+    )
+    .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))?;
+
+    let (width, pixels) = image.layer_data.channel_data.pixels;
+    Ok((width, pixels.len() / width.max(1), pixels))
+}
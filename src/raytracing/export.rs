@@ -0,0 +1,282 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use image::{codecs::hdr::HdrEncoder, ColorType, Rgb};
+
+use crate::primitive::color::Color;
+
+use super::tonemap::ToneMapper;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    Ppm,
+    Exr,
+    Hdr,
+    Pfm,
+}
+
+impl ImageFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "png" => Some(ImageFormat::Png),
+            "ppm" => Some(ImageFormat::Ppm),
+            "exr" => Some(ImageFormat::Exr),
+            "hdr" => Some(ImageFormat::Hdr),
+            "pfm" => Some(ImageFormat::Pfm),
+            _ => None,
+        }
+    }
+
+    pub fn is_floating_point(&self) -> bool {
+        match self {
+            ImageFormat::Png | ImageFormat::Ppm => false,
+            ImageFormat::Exr | ImageFormat::Hdr | ImageFormat::Pfm => true,
+        }
+    }
+
+    pub fn get_extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Exr => "exr",
+            ImageFormat::Hdr => "hdr",
+            ImageFormat::Pfm => "pfm",
+        }
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// 8-bit formats get the tone mapped display values, the floating point formats keep the linear
+// radiance untouched so it can be graded later.
+pub fn write_image(
+    path: &Path,
+    format: ImageFormat,
+    width: u32,
+    height: u32,
+    data: &[Color],
+    tone_mapper: &ToneMapper,
+) -> io::Result<()> {
+    match format {
+        ImageFormat::Png => write_png(path, width, height, data, tone_mapper),
+        ImageFormat::Ppm => write_ppm(path, width, height, data, tone_mapper),
+        ImageFormat::Exr => write_exr(path, width, height, data),
+        ImageFormat::Hdr => write_hdr(path, width, height, data),
+        ImageFormat::Pfm => write_pfm(path, width, height, data),
+    }
+}
+
+fn write_png(
+    path: &Path,
+    width: u32,
+    height: u32,
+    data: &[Color],
+    tone_mapper: &ToneMapper,
+) -> io::Result<()> {
+    let mut bytes = Vec::with_capacity((3 * width * height) as usize);
+    for color in top_down_rows(width, height, data) {
+        bytes.extend_from_slice(&to_bytes(&tone_mapper.map(color)));
+    }
+
+    image::save_buffer_with_format(
+        path,
+        &bytes,
+        width,
+        height,
+        ColorType::Rgb8,
+        image::ImageFormat::Png,
+    )
+    .map_err(to_io_error)
+}
+
+fn write_ppm(
+    path: &Path,
+    width: u32,
+    height: u32,
+    data: &[Color],
+    tone_mapper: &ToneMapper,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write!(writer, "P6\n{} {}\n255\n", width, height)?;
+
+    for color in top_down_rows(width, height, data) {
+        writer.write_all(&to_bytes(&tone_mapper.map(color)))?;
+    }
+
+    writer.flush()
+}
+
+fn write_exr(path: &Path, width: u32, height: u32, data: &[Color]) -> io::Result<()> {
+    exr::prelude::write_rgb_file(path, width as usize, height as usize, |x, y| {
+        let color = data[((height as usize - 1 - y) * width as usize) + x];
+        (color.r, color.g, color.b)
+    })
+    .map_err(to_io_error)
+}
+
+// Pixels of one layer of an EXR file.
+pub enum ExrLayer<'a> {
+    Color(&'a [Color]),
+    // Integers in a single channel, kept exact where colors would be rounded and filtered.
+    Id(&'a [u32]),
+}
+
+// Writes several named images as the layers of one EXR file.
+pub fn write_exr_layers(
+    path: &Path,
+    width: u32,
+    height: u32,
+    layers: &[(&str, ExrLayer)],
+) -> io::Result<()> {
+    use exr::prelude::{
+        AnyChannel, AnyChannels, Encoding, FlatSamples, Image, ImageAttributes, IntegerBounds,
+        Layer, LayerAttributes, WritableImage,
+    };
+
+    let size = (width as usize, height as usize);
+    let exr_layers: Vec<_> = layers
+        .iter()
+        .map(|(name, data)| {
+            let channels = match data {
+                ExrLayer::Color(data) => {
+                    let mut channels = [Vec::new(), Vec::new(), Vec::new()];
+                    for color in top_down_rows(width, height, data) {
+                        channels[0].push(color.r);
+                        channels[1].push(color.g);
+                        channels[2].push(color.b);
+                    }
+                    let [red, green, blue] = channels;
+
+                    vec![
+                        AnyChannel::new("R", FlatSamples::F32(red)),
+                        AnyChannel::new("G", FlatSamples::F32(green)),
+                        AnyChannel::new("B", FlatSamples::F32(blue)),
+                    ]
+                }
+                ExrLayer::Id(ids) => {
+                    let ids = top_down_rows(width, height, ids).copied().collect();
+                    vec![AnyChannel::new("id", FlatSamples::U32(ids))]
+                }
+            };
+
+            Layer::new(
+                size,
+                LayerAttributes::named(*name),
+                Encoding::FAST_LOSSLESS,
+                AnyChannels::sort(channels.into()),
+            )
+        })
+        .collect();
+
+    Image::from_layers(
+        ImageAttributes::new(IntegerBounds::from_dimensions(size)),
+        exr_layers,
+    )
+    .write()
+    .to_file(path)
+    .map_err(to_io_error)
+}
+
+fn write_hdr(path: &Path, width: u32, height: u32, data: &[Color]) -> io::Result<()> {
+    let pixels: Vec<Rgb<f32>> = top_down_rows(width, height, data)
+        .map(|color| Rgb([color.r, color.g, color.b]))
+        .collect();
+
+    let writer = BufWriter::new(File::create(path)?);
+    HdrEncoder::new(writer)
+        .encode(&pixels, width as usize, height as usize)
+        .map_err(to_io_error)
+}
+
+fn write_pfm(path: &Path, width: u32, height: u32, data: &[Color]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    // A negative scale marks little-endian samples. PFM stores the bottom row first, like the
+    // texture, so the data is written in its original order.
+    write!(writer, "PF\n{} {}\n-1.0\n", width, height)?;
+    for color in data {
+        for value in &[color.r, color.g, color.b] {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+    }
+
+    writer.flush()
+}
+
+// The texture is stored bottom row first, most image files expect the top row first.
+fn top_down_rows<'a, T>(
+    width: u32,
+    height: u32,
+    data: &'a [T],
+) -> impl Iterator<Item = &'a T> + 'a {
+    (0..height)
+        .rev()
+        .flat_map(move |y| data[(y * width) as usize..((y + 1) * width) as usize].iter())
+}
+
+fn to_bytes(color: &Color) -> [u8; 3] {
+    [to_byte(color.r), to_byte(color.g), to_byte(color.b)]
+}
+
+fn to_byte(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn to_io_error(error: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, error.to_string())
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exr::prelude::{read_all_flat_layers_from_file, FlatSamples};
+
+    #[test]
+    fn writes_ids_as_single_integer_channel() {
+        let path = std::env::temp_dir().join(format!("rustracing-ids-{}.exr", std::process::id()));
+        let colors = vec![Color::new(0.5, 0.25, 1.0); 6];
+        // Rows are stored bottom up, the file has them top down.
+        let ids = [0, 1, 2, 3, 7, u32::MAX];
+        write_exr_layers(
+            &path,
+            3,
+            2,
+            &[
+                ("beauty", ExrLayer::Color(&colors)),
+                ("object_id", ExrLayer::Id(&ids)),
+            ],
+        )
+        .unwrap();
+        let image = read_all_flat_layers_from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let layer = image
+            .layer_data
+            .iter()
+            .find(|layer| {
+                layer
+                    .attributes
+                    .layer_name
+                    .as_ref()
+                    .map(|name| name.to_string())
+                    == Some(String::from("object_id"))
+            })
+            .expect("the ids should have a layer");
+        let channels = &layer.channel_data.list;
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[0].name.to_string(), "id");
+        match &channels[0].sample_data {
+            FlatSamples::U32(samples) => assert_eq!(samples, &vec![3, 7, u32::MAX, 0, 1, 2]),
+            _ => panic!("the ids should be stored as integers"),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use crate::primitive::{color::Color, vector::Vector};
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Light reaching a point from a single direction.
+pub struct LightSample {
+    // Unit direction from the point towards the light.
+    pub direction: Vector,
+    // Infinite for lights that are infinitely far away.
+    pub distance: f32,
+    // Irradiance on a surface facing the light.
+    pub irradiance: Color,
+}
+
+/*
+ * Lights without a surface, shining from a single point or direction. No ray can hit them by
+ * chance, so they're only seen through shadow rays cast towards them from each shaded point.
+ */
+pub trait Light: Sync + Send {
+    fn sample(&self, position: &Vector) -> Option<LightSample>;
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Shines equally in every direction, fading with the square of the distance.
+pub struct PointLight {
+    position: Vector,
+    intensity: Color,
+}
+
+impl PointLight {
+    pub fn new(position: Vector, color: Color, intensity: f32) -> Self {
+        Self {
+            position,
+            intensity: color.times(intensity),
+        }
+    }
+}
+
+impl Light for PointLight {
+    fn sample(&self, position: &Vector) -> Option<LightSample> {
+        sample_from_point(&self.position, &self.intensity, position)
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Point light shining in a cone, at full strength within `inner_angle` of its axis and fading
+// smoothly to nothing at `outer_angle`.
+pub struct SpotLight {
+    position: Vector,
+    direction: Vector,
+    intensity: Color,
+    cos_inner: f32,
+    cos_outer: f32,
+}
+
+impl SpotLight {
+    pub fn new(
+        position: Vector,
+        direction: Vector,
+        color: Color,
+        intensity: f32,
+        inner_angle: f32,
+        outer_angle: f32,
+    ) -> Self {
+        Self {
+            position,
+            direction: direction.normalized(),
+            intensity: color.times(intensity),
+            cos_inner: inner_angle.min(outer_angle).cos(),
+            cos_outer: outer_angle.cos(),
+        }
+    }
+}
+
+impl Light for SpotLight {
+    fn sample(&self, position: &Vector) -> Option<LightSample> {
+        let sample = sample_from_point(&self.position, &self.intensity, position)?;
+        let cos_angle = -sample.direction.dot(&self.direction);
+        if cos_angle <= self.cos_outer {
+            return None;
+        }
+
+        let falloff = if cos_angle >= self.cos_inner {
+            1.0
+        } else {
+            let t = (cos_angle - self.cos_outer) / (self.cos_inner - self.cos_outer);
+            t * t * (3.0 - 2.0 * t)
+        };
+
+        Some(LightSample {
+            irradiance: sample.irradiance.times(falloff),
+            ..sample
+        })
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Parallel light travelling along `direction` from infinitely far away, as sunlight does.
+pub struct DirectionalLight {
+    to_light: Vector,
+    irradiance: Color,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vector, color: Color, intensity: f32) -> Self {
+        Self {
+            to_light: direction.normalized().times(-1.0),
+            irradiance: color.times(intensity),
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample(&self, _position: &Vector) -> Option<LightSample> {
+        Some(LightSample {
+            direction: self.to_light,
+            distance: f32::INFINITY,
+            irradiance: self.irradiance,
+        })
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+fn sample_from_point(light: &Vector, intensity: &Color, position: &Vector) -> Option<LightSample> {
+    let to_light = light.minus(position);
+    let distance_sqr = to_light.len_sqr();
+    if distance_sqr == 0.0 {
+        return None;
+    }
+
+    Some(LightSample {
+        direction: to_light.normalized(),
+        distance: distance_sqr.sqrt(),
+        irradiance: intensity.times(1.0 / distance_sqr),
+    })
+}
@@ -0,0 +1,107 @@
+use crate::{
+    material::medium::Medium,
+    primitive::{ray::Ray, vector::Vector},
+};
+
+const VACUUM_INDEX_OF_REFRACTION: f32 = 1.0;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[derive(Clone)]
+struct MediumEntry {
+    object_id: usize,
+    index_of_refraction: Option<f32>,
+    medium: Option<Medium>,
+}
+
+// The media a path is currently inside of, innermost last, so nested objects such as glass in
+// water refract against the medium that actually surrounds them.
+#[derive(Clone, Default)]
+pub struct MediumStack {
+    entries: Vec<MediumEntry>,
+}
+
+impl MediumStack {
+    pub fn get_surrounding_index_of_refraction(&self, object_id: usize) -> f32 {
+        self.entries
+            .iter()
+            .rev()
+            .filter(|entry| entry.object_id != object_id)
+            .find_map(|entry| entry.index_of_refraction)
+            .unwrap_or(VACUUM_INDEX_OF_REFRACTION)
+    }
+
+    pub fn is_outside(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Medium of the innermost object the path is in, objects without one are clear inside.
+    pub fn get_medium(&self) -> Option<&Medium> {
+        self.entries.last().and_then(|entry| entry.medium.as_ref())
+    }
+
+    pub fn enter(
+        &mut self,
+        object_id: usize,
+        index_of_refraction: Option<f32>,
+        medium: Option<Medium>,
+    ) {
+        self.entries.push(MediumEntry {
+            object_id,
+            index_of_refraction,
+            medium,
+        });
+    }
+
+    pub fn exit(&mut self, object_id: usize) {
+        if let Some(position) = self
+            .entries
+            .iter()
+            .rposition(|entry| entry.object_id == object_id)
+        {
+            self.entries.remove(position);
+        }
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+/*
+ * Medium filling the space outside of every object, such as haze or fog over a landscape. It
+ * reaches `radius` away from the origin, the environment lies beyond it and lights the medium
+ * from there.
+ */
+pub struct Atmosphere {
+    medium: Medium,
+    radius: f32,
+}
+
+impl Atmosphere {
+    pub fn new(medium: Medium, radius: f32) -> Self {
+        Self { medium, radius }
+    }
+
+    pub fn get_medium(&self) -> &Medium {
+        &self.medium
+    }
+
+    // Distances along the ray, up to `max_distance`, between which it crosses the atmosphere.
+    pub fn get_segment(&self, ray: &Ray, max_distance: f32) -> Option<(f32, f32)> {
+        let direction = ray.direction.normalized();
+        let to_origin = Vector::new(0.0, 0.0, 0.0).minus(&ray.origin);
+        let middle = to_origin.dot(&direction);
+        let half_chord_sqr = self.radius * self.radius - (to_origin.len_sqr() - middle * middle);
+        if half_chord_sqr <= 0.0 {
+            return None;
+        }
+
+        let half_chord = half_chord_sqr.sqrt();
+        let start = (middle - half_chord).max(0.0);
+        let end = (middle + half_chord).min(max_distance);
+        if start < end {
+            Some((start, end))
+        } else {
+            None
+        }
+    }
+}
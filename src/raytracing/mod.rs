@@ -1,3 +1,15 @@
+pub mod adaptive;
+pub mod aov;
+pub mod benchmark;
+#[cfg(test)]
+mod convergence;
+mod denoise;
+pub mod environment;
+pub mod export;
+pub mod light;
+pub mod medium;
 pub mod runner;
 mod scene;
 mod texture;
+pub mod tile;
+pub mod tonemap;
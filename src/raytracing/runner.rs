@@ -1,9 +1,18 @@
-use std::sync::{Arc, RwLock};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "window")]
+use std::time::{SystemTime, UNIX_EPOCH};
 
+#[cfg(feature = "window")]
 use glium::{
     glutin::{
         dpi::PhysicalPosition,
-        event::{Event, MouseScrollDelta},
+        event::{ElementState, Event, KeyboardInput, MouseScrollDelta, VirtualKeyCode},
     },
     Display, Texture2d,
 };
@@ -11,17 +20,41 @@ use threadpool::ThreadPool;
 
 use crate::{
     camera::OrbitalCamera,
-    object::{Object, PhysicalObject, Shape, ShapeProperties},
-    primitive::{color::Color, vector::Vector},
-    texture::TextureGenerator,
+    object::{instance::Instance, Object, PhysicalObject, Shape, ShapeProperties},
+    primitive::transform::Transform,
+    sampler::SamplerKind,
 };
 
+#[cfg(test)]
+use crate::primitive::color::Color;
+#[cfg(feature = "window")]
+use crate::texture::TextureGenerator;
+
 use super::{
+    adaptive::{self, AdaptiveSampling},
+    environment::Environment,
+    export::ImageFormat,
+    light::Light,
+    medium::Atmosphere,
     scene::RaytracingScene,
-    texture::{ColorColumnRange, IncrementalTextureHandle},
+    texture::IncrementalTextureHandle,
+    tile::{create_tiles, PixelSums, Tile, TileOrder, TileResult},
+    tonemap::ToneMapper,
 };
 
+#[cfg(feature = "window")]
 const CAMERA_ROTATION_MULTIPLIER: f32 = 0.005;
+const DEFAULT_TILE_SIZE: u32 = 32;
+#[cfg(feature = "window")]
+const EXPOSURE_STEP: f32 = 0.5;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[derive(Clone, Copy, Debug)]
+pub enum RenderBudget {
+    SampleCount(u32),
+    Duration(Duration),
+}
 
 /*-----------------------------------------------------------------------------------------------*/
 
@@ -29,91 +62,327 @@ pub struct RaytracingRunner {
     width: u32,
     height: u32,
 
+    #[cfg(feature = "window")]
     mouse_pressed: bool,
+    #[cfg(feature = "window")]
     previous_mouse_position: Option<PhysicalPosition<f64>>,
 
     camera: OrbitalCamera,
+    seed: u64,
+    sampler_kind: SamplerKind,
 
-    work_pool: ThreadPool<ColorColumnRange>,
-    scene: Arc<RwLock<RaytracingScene>>,
+    tiles: Vec<Tile>,
+    samples_per_pass: u32,
+    adaptive_sampling: Option<AdaptiveSampling>,
+
+    work_pool: ThreadPool<TileResult>,
+    // Jobs share the scene without locking, it can only change while no frame is rendering.
+    scene: Arc<RaytracingScene>,
     texture_handle: IncrementalTextureHandle,
 }
 
 impl RaytracingRunner {
-    pub fn new(width: u32, height: u32, focus: Vector) -> Self {
-        let camera = OrbitalCamera::new(width, height, focus, 1.0, 0.02);
+    pub fn new(
+        width: u32,
+        height: u32,
+        camera: OrbitalCamera,
+        min_bounce_count: usize,
+        max_bounce_count: usize,
+    ) -> Self {
         Self {
             width,
             height,
+            #[cfg(feature = "window")]
             mouse_pressed: false,
+            #[cfg(feature = "window")]
             previous_mouse_position: None,
 
             camera,
+            seed: 0,
+            sampler_kind: SamplerKind::Sobol,
+
+            tiles: create_tiles(width, height, DEFAULT_TILE_SIZE, TileOrder::Spiral),
+            samples_per_pass: 1,
+            adaptive_sampling: None,
 
             work_pool: ThreadPool::new(16),
-            scene: Arc::new(RwLock::new(RaytracingScene::new(4))),
+            scene: Arc::new(RaytracingScene::new(min_bounce_count, max_bounce_count)),
             texture_handle: IncrementalTextureHandle::new(width, height, 100000),
         }
     }
 
     pub fn add_object(&mut self, properties: ShapeProperties, shape: impl Shape + 'static) {
-        self.scene
-            .write()
-            .unwrap()
+        Arc::get_mut(&mut self.scene)
+            .expect("objects can't be added while a frame is rendering")
             .add_object(PhysicalObject::new(properties, shape));
     }
 
+    // Places a copy of a shape that other instances may share, its geometry isn't duplicated.
+    pub fn add_instance(
+        &mut self,
+        properties: ShapeProperties,
+        shape: Arc<dyn Shape>,
+        transform: Transform,
+    ) {
+        Arc::get_mut(&mut self.scene)
+            .expect("objects can't be added while a frame is rendering")
+            .add_object(Instance::new(properties, shape, transform));
+    }
+
+    pub fn add_light(&mut self, light: impl Light + 'static) {
+        Arc::get_mut(&mut self.scene)
+            .expect("lights can't be added while a frame is rendering")
+            .add_light(light);
+    }
+
+    pub fn set_environment(&mut self, environment: Option<Environment>) {
+        Arc::get_mut(&mut self.scene)
+            .expect("the environment can't change while a frame is rendering")
+            .set_environment(environment);
+        self.invalidate_image();
+    }
+
+    pub fn set_atmosphere(&mut self, atmosphere: Option<Atmosphere>) {
+        Arc::get_mut(&mut self.scene)
+            .expect("the atmosphere can't change while a frame is rendering")
+            .set_atmosphere(atmosphere);
+        self.invalidate_image();
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.invalidate_image();
+    }
+
+    pub fn set_sampler_kind(&mut self, sampler_kind: SamplerKind) {
+        self.sampler_kind = sampler_kind;
+        self.invalidate_image();
+    }
+
+    pub fn set_tiling(&mut self, tile_size: u32, order: TileOrder, samples_per_pass: u32) {
+        self.tiles = create_tiles(self.width, self.height, tile_size, order);
+        self.samples_per_pass = samples_per_pass;
+        self.invalidate_image();
+    }
+
+    pub fn set_adaptive_sampling(&mut self, adaptive_sampling: Option<AdaptiveSampling>) {
+        self.adaptive_sampling = adaptive_sampling;
+        self.invalidate_image();
+    }
+
+    pub fn set_denoising(&mut self, denoises: bool) {
+        self.texture_handle.set_denoising(denoises);
+    }
+
+    pub fn set_tone_mapper(&mut self, tone_mapper: ToneMapper) {
+        self.texture_handle.set_tone_mapper(tone_mapper);
+    }
+
+    // Renders until the budget is spent or every tile is below the noise threshold. A sample count
+    // budget is spent as a total over the image, so adaptive sampling moves samples between tiles
+    // without changing the total. Returns the average samples per pixel.
+    pub fn render(&mut self, budget: RenderBudget) -> f32 {
+        let start = Instant::now();
+        let pixel_count = (self.width * self.height) as u64;
+        let mut traced_sample_count = 0;
+        loop {
+            let pass_sample_count = match budget {
+                RenderBudget::SampleCount(count) => {
+                    let remaining =
+                        (count as u64 * pixel_count).saturating_sub(traced_sample_count);
+                    let remaining_per_pixel = (remaining + pixel_count - 1) / pixel_count;
+                    self.samples_per_pass.min(remaining_per_pixel as u32)
+                }
+                RenderBudget::Duration(duration) if start.elapsed() < duration => {
+                    self.samples_per_pass
+                }
+                RenderBudget::Duration(_) => 0,
+            };
+            if pass_sample_count == 0 {
+                break;
+            }
+
+            let pass_traced_sample_count = self.start_calculating_next_image(pass_sample_count);
+            self.collect_image();
+            if pass_traced_sample_count == 0 {
+                break;
+            }
+            traced_sample_count += pass_traced_sample_count;
+        }
+
+        traced_sample_count as f32 / pixel_count as f32
+    }
+
+    #[cfg(test)]
+    pub fn get_image_data(&self) -> &[Color] {
+        self.texture_handle.get_data()
+    }
+
+    pub fn save_image(&self, path: &Path, format: ImageFormat) -> io::Result<()> {
+        self.texture_handle.save(path, format)
+    }
+
+    // Returns the files written, the EXR layers all go into the image's own file.
+    pub fn save_aovs(&self, path: &Path, format: ImageFormat) -> io::Result<Vec<PathBuf>> {
+        self.texture_handle.save_aovs(path, format)
+    }
+
+    pub fn save_heatmap(&self, path: &Path, format: ImageFormat) -> io::Result<()> {
+        self.texture_handle.save_heatmap(path, format)
+    }
+
     fn collect_image(&mut self) {
-        for color_range in self.work_pool.collect_results() {
-            self.texture_handle.add_color_range(color_range);
+        for tile_result in self.work_pool.collect_results() {
+            self.texture_handle.add_tile_result(tile_result);
+        }
+    }
+
+    // Samples each tile gets this pass, shifted towards the noisy tiles with adaptive sampling.
+    fn get_tile_sample_counts(&self, sample_count: u32) -> Vec<u32> {
+        match self.adaptive_sampling {
+            Some(adaptive_sampling) => {
+                let tile_errors: Vec<f32> = self
+                    .tiles
+                    .iter()
+                    .map(|tile| {
+                        self.texture_handle
+                            .get_tile_error(tile, adaptive_sampling.min_sample_count)
+                    })
+                    .collect();
+                adaptive::distribute_samples(
+                    &self.tiles,
+                    &tile_errors,
+                    adaptive_sampling.noise_threshold,
+                    sample_count,
+                )
+            }
+            None => vec![sample_count; self.tiles.len()],
         }
     }
 
-    fn start_calculating_next_image(&mut self) {
+    // Returns the number of samples traced over the whole image.
+    fn start_calculating_next_image(&mut self, sample_count: u32) -> u64 {
+        if let Some(scene) = Arc::get_mut(&mut self.scene) {
+            scene.update_bvh();
+        }
+
+        let tile_sample_counts = self.get_tile_sample_counts(sample_count);
+        let mut traced_sample_count = 0;
+
         let half_width = (self.width / 2) as i32;
         let half_height = (self.height / 2) as i32;
 
-        let width_thread_chunk = 1;
+        let width = self.width;
+        let seed = self.seed;
+        let sampler_kind = self.sampler_kind;
+        for (tile, sample_count) in self.tiles.iter_mut().zip(tile_sample_counts) {
+            if sample_count == 0 {
+                continue;
+            }
 
-        let mut x_range: [u32; 2] = [0, 0];
-        let height = self.height;
-        while x_range[1] < self.width {
-            x_range[1] = self.width.min(x_range[0] + width_thread_chunk);
-
-            let x_range_to_cover = x_range.clone();
             let scene = Arc::clone(&self.scene);
-
             let camera = self.camera;
-            self.work_pool.run(move || -> ColorColumnRange {
-                let mut color_range = ColorColumnRange {
-                    starting_column: x_range_to_cover[0],
-                    color_columns: Vec::new(),
-                };
-
-                for x in x_range_to_cover[0]..x_range_to_cover[1] {
-                    let mut color_column = Vec::<Color>::with_capacity(height as usize);
-                    for y in 0..height {
-                        let ray = camera
-                            .sample_pixel_ray([x as i32 - half_width, y as i32 - half_height]);
-
-                        let color = scene.read().unwrap().get_pixel_color(ray);
-                        color_column.push(color);
+            let pass_tile = *tile;
+            self.work_pool.run(move || -> TileResult {
+                let first_sample_index = pass_tile.sample_count as u64;
+                let mut pixel_sums = Vec::with_capacity(pass_tile.get_pixel_count());
+                for y in pass_tile.y..(pass_tile.y + pass_tile.height) {
+                    for x in pass_tile.x..(pass_tile.x + pass_tile.width) {
+                        let pixel_index = (y * width + x) as u64;
+                        let mut sums = PixelSums::default();
+                        for sample_index in
+                            first_sample_index..(first_sample_index + sample_count as u64)
+                        {
+                            let mut sampler = sampler_kind.create(seed, pixel_index, sample_index);
+                            let ray = camera.sample_pixel_ray(
+                                [x as i32 - half_width, y as i32 - half_height],
+                                sampler.as_mut(),
+                            );
+
+                            sums.add(&scene.get_pixel_sample(ray, sampler.as_mut()));
+                        }
+                        pixel_sums.push(sums);
                     }
-                    color_range.color_columns.push(color_column);
                 }
 
-                color_range
+                TileResult {
+                    tile: pass_tile,
+                    sample_count,
+                    pixel_sums,
+                }
             });
 
-            x_range[0] += width_thread_chunk;
+            tile.sample_count += sample_count;
+            traced_sample_count += sample_count as u64 * pass_tile.get_pixel_count() as u64;
         }
+
+        traced_sample_count
     }
 
     fn invalidate_image(&mut self) {
         self.texture_handle.invalidate();
+        for tile in &mut self.tiles {
+            tile.sample_count = 0;
+        }
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[cfg(feature = "window")]
+impl RaytracingRunner {
+    fn save_snapshot(&self) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        for format in &[ImageFormat::Png, ImageFormat::Exr] {
+            let path = PathBuf::from(format!("snapshot-{}.{}", timestamp, format.get_extension()));
+            match self.save_image(&path, *format) {
+                Ok(()) => println!("Saved snapshot to {}", path.display()),
+                Err(error) => eprintln!("Could not save {}: {}", path.display(), error),
+            }
+        }
+    }
+
+    fn handle_key(&mut self, keycode: VirtualKeyCode) {
+        let mut tone_mapper = self.texture_handle.get_tone_mapper();
+        match keycode {
+            VirtualKeyCode::S => self.save_snapshot(),
+            VirtualKeyCode::A => match self.texture_handle.cycle_aov() {
+                Some(aov) => println!("Showing {}", aov),
+                None => println!("Showing the image"),
+            },
+            VirtualKeyCode::D => {
+                let denoises = self.texture_handle.toggle_denoising();
+                println!("Denoising: {}", if denoises { "on" } else { "off" });
+            }
+            VirtualKeyCode::H => {
+                let shows_heatmap = self.texture_handle.toggle_heatmap();
+                println!(
+                    "Sample heatmap: {}",
+                    if shows_heatmap { "on" } else { "off" }
+                );
+            }
+            VirtualKeyCode::T => tone_mapper.operator = tone_mapper.operator.next(),
+            VirtualKeyCode::Equals => tone_mapper.exposure += EXPOSURE_STEP,
+            VirtualKeyCode::Minus => tone_mapper.exposure -= EXPOSURE_STEP,
+            _ => return,
+        }
+
+        // Tone mapping happens when the texture is built, the accumulated samples stay valid.
+        if tone_mapper != self.texture_handle.get_tone_mapper() {
+            println!(
+                "Tone mapping: {}, exposure {:+.1}",
+                tone_mapper.operator, tone_mapper.exposure
+            );
+            self.texture_handle.set_tone_mapper(tone_mapper);
+        }
     }
 }
 
+#[cfg(feature = "window")]
 impl TextureGenerator for RaytracingRunner {
     fn get_texture_size(&self) -> [u32; 2] {
         [self.width, self.height]
@@ -122,7 +391,7 @@ impl TextureGenerator for RaytracingRunner {
     fn update_texture(&mut self, display: &Display) -> Texture2d {
         self.collect_image();
         let texture = self.texture_handle.get_texture(display);
-        self.start_calculating_next_image();
+        self.start_calculating_next_image(self.samples_per_pass);
 
         texture
     }
@@ -132,8 +401,8 @@ impl TextureGenerator for RaytracingRunner {
             Event::WindowEvent { event, .. } => match event {
                 glium::glutin::event::WindowEvent::MouseInput { state, .. } => {
                     match state {
-                        glium::glutin::event::ElementState::Pressed => self.mouse_pressed = true,
-                        glium::glutin::event::ElementState::Released => {
+                        ElementState::Pressed => self.mouse_pressed = true,
+                        ElementState::Released => {
                             self.mouse_pressed = false;
                             self.previous_mouse_position = None
                         }
@@ -165,6 +434,15 @@ impl TextureGenerator for RaytracingRunner {
                     self.camera.delta_zoom(y);
                     self.invalidate_image();
                 }
+                glium::glutin::event::WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(keycode),
+                            ..
+                        },
+                    ..
+                } => self.handle_key(*keycode),
                 _ => (),
             },
             _ => (),
@@ -1,103 +1,678 @@
-use std::f32::consts::PI;
-
 use crate::{
-    object::{Object, ShapeType},
-    primitive::{color::Color, contact::RayContact, ray::Ray},
+    material::{medium::Medium, Material},
+    object::{bvh::Bvh, Object},
+    primitive::{color::Color, contact::RayContact, ray::Ray, vector::Vector},
+    sampler::Sampler,
 };
 
-fn get_refracted_angle_delta(
-    incident_angle: f32,
-    incident_index: f32,
-    refracted_index: f32,
-) -> f32 {
-    let refracted_angle_sin = (incident_index / refracted_index) * incident_angle.sin();
-    if refracted_angle_sin < 1.0 {
-        incident_angle - refracted_angle_sin.asin()
-    } else {
-        -2.0 * ((PI / 2.0) - incident_angle)
+use super::{
+    environment::Environment,
+    light::{Light, LightSample},
+    medium::{Atmosphere, MediumStack},
+};
+
+// Relative tolerance when checking that a shadow ray reached the sampled point on a light.
+const SHADOW_RAY_TOLERANCE: f32 = 0.001;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Radiance of a camera path, with what its first hit saw for the denoiser and the debug views.
+// Paths that escape the scene leave those at zero and without an object.
+pub struct PixelSample {
+    pub color: Color,
+    pub albedo: Color,
+    pub normal: Vector,
+    pub distance: f32,
+    pub object_id: Option<usize>,
+    pub bounce_count: u32,
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Shadow ray past the invisible surfaces in its way, whose first hit is the one that counts.
+struct ShadowPath {
+    ray: Ray,
+    // Media the ray starts in.
+    media: MediumStack,
+    // Of the media crossed so far.
+    transmittance: Color,
+    // Covered so far, the ray starts this far away from the shaded point.
+    distance: f32,
+}
+
+impl ShadowPath {
+    // Transmittance from the shaded point to `distance` along what's left of the ray.
+    fn get_transmittance(&self, scene: &RaytracingScene, distance: f32) -> Color {
+        self.transmittance
+            .filter(&scene.get_transmittance(&self.media, &self.ray, distance))
     }
 }
 
+/*-----------------------------------------------------------------------------------------------*/
+
 pub struct RaytracingScene {
     objects: Vec<Box<dyn Object>>,
 
+    bvh: Bvh,
+    bvh_object_ids: Vec<usize>,
+    unbounded_object_ids: Vec<usize>,
+    is_bvh_outdated: bool,
+
+    emitter_object_ids: Vec<usize>,
+    lights: Vec<Box<dyn Light>>,
+    // Seen by rays that leave the scene, which otherwise find darkness.
+    environment: Option<Environment>,
+    // Fills the space outside of the objects.
+    atmosphere: Option<Atmosphere>,
+
+    // Paths are only cut short by Russian roulette after `min_bounce_count` bounces, the maximum
+    // is a safety cap for paths that would otherwise keep all of their energy.
+    min_bounce_count: usize,
     max_bounce_count: usize,
 }
 
 impl RaytracingScene {
-    pub fn new(max_bounce_count: usize) -> Self {
+    pub fn new(min_bounce_count: usize, max_bounce_count: usize) -> Self {
         Self {
             objects: Vec::new(),
+
+            bvh: Bvh::empty(),
+            bvh_object_ids: Vec::new(),
+            unbounded_object_ids: Vec::new(),
+            is_bvh_outdated: false,
+
+            emitter_object_ids: Vec::new(),
+            lights: Vec::new(),
+            environment: None,
+            atmosphere: None,
+
+            min_bounce_count,
             max_bounce_count,
         }
     }
 
     pub fn add_object<O: Object + 'static>(&mut self, object: O) {
+        if object.get_properties().material.is_emissive() && object.get_surface_area().is_some() {
+            self.emitter_object_ids.push(self.objects.len());
+        }
+
         self.objects.push(Box::new(object));
+        self.is_bvh_outdated = true;
     }
 
-    pub fn get_pixel_color(&self, ray: Ray) -> Color {
-        self.project_ray(ray, self.max_bounce_count)
+    pub fn add_light<L: Light + 'static>(&mut self, light: L) {
+        self.lights.push(Box::new(light));
     }
 
-    fn project_ray(&self, ray: Ray, bounces_left: usize) -> Color {
-        if bounces_left == 0 {
-            Color::zero()
-        } else if let Some(contact) = self.find_closest_contact(&ray) {
-            let object = &self.objects[contact.get_object_id()];
-            let properties = object.get_properties();
+    pub fn set_environment(&mut self, environment: Option<Environment>) {
+        self.environment = environment;
+    }
+
+    pub fn set_atmosphere(&mut self, atmosphere: Option<Atmosphere>) {
+        self.atmosphere = atmosphere;
+    }
+
+    pub fn update_bvh(&mut self) {
+        if !self.is_bvh_outdated {
+            return;
+        }
+
+        self.bvh_object_ids.clear();
+        self.unbounded_object_ids.clear();
+
+        let mut bounding_boxes = Vec::with_capacity(self.objects.len());
+        for (object_id, object) in self.objects.iter().enumerate() {
+            let bounding_box = object.get_bounding_box();
+            if bounding_box.is_finite() {
+                bounding_boxes.push(bounding_box);
+                self.bvh_object_ids.push(object_id);
+            } else {
+                self.unbounded_object_ids.push(object_id);
+            }
+        }
+
+        self.bvh = Bvh::build(&bounding_boxes);
+        self.is_bvh_outdated = false;
+    }
+
+    pub fn get_pixel_sample(&self, ray: Ray, sampler: &mut dyn Sampler) -> PixelSample {
+        self.project_ray(ray, sampler)
+    }
+
+    fn project_ray(&self, ray: Ray, sampler: &mut dyn Sampler) -> PixelSample {
+        let mut pixel_sample = PixelSample {
+            color: Color::zero(),
+            albedo: Color::zero(),
+            normal: Vector::new(0.0, 0.0, 0.0),
+            distance: 0.0,
+            object_id: None,
+            bounce_count: 0,
+        };
+        let mut color = Color::zero();
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        let mut media = MediumStack::default();
+
+        // Pdf of the last sampled direction, `None` when it can't be sampled by lights either.
+        let mut previous_pdf: Option<f32> = None;
+        // Distance covered since that direction was sampled, across invisible surfaces.
+        let mut travelled_distance = 0.0;
+
+        let mut ray = ray;
+        for bounce_count in 1..=self.max_bounce_count {
+            let contact = self.find_closest_contact(&ray);
+            let surface_distance = contact
+                .as_ref()
+                .map_or(f32::INFINITY, |contact| contact.get_distance_from_origin());
+
+            if let Some((medium, start, end)) =
+                self.get_medium_segment(&media, &ray, surface_distance)
+            {
+                let interaction = medium.sample_interaction(end - start, sampler);
+                throughput = throughput.filter(&interaction.weight);
 
-            match properties.shape_type {
-                ShapeType::Emitter => properties.color.clone(),
-                ShapeType::Reflector {
-                    transparency,
-                    roughness,
-                    density,
-                } => {
-                    let from_inside = contact.is_from_inside();
+                if let Some(distance) = interaction.scatter_distance {
+                    pixel_sample.bounce_count = bounce_count as u32;
 
-                    if from_inside {
-                        let inside_refraction_ray = contact.get_refraction(density, 1.0);
-                        self.project_ray(inside_refraction_ray, bounces_left - 1)
+                    // Points inside a medium have no surface, the contact faces back along the ray.
+                    let position = ray
+                        .origin
+                        .plus(&ray.direction.normalized_to(start + distance));
+                    let scatter_contact =
+                        RayContact::new(position, ray.direction.times(-1.0), &ray);
+                    let phase_function = medium.get_phase_function();
+                    color = color.plus(
+                        &self
+                            .sample_emitters(&phase_function, &scatter_contact, &media, sampler)
+                            .filter(&throughput),
+                    );
+
+                    let sample = match phase_function.scatter(&scatter_contact, sampler) {
+                        Some(sample) => sample,
+                        None => break,
+                    };
+                    previous_pdf = Some(sample.pdf);
+                    travelled_distance = 0.0;
+                    ray = sample.ray;
+
+                    if !self.survives_roulette(bounce_count, &mut throughput, sampler) {
+                        break;
+                    }
+                    continue;
+                }
+            }
+
+            let mut contact = match contact {
+                Some(contact) => contact,
+                None => {
+                    if let Some(environment) = &self.environment {
+                        let direction = ray.direction.normalized();
+                        let weight = match previous_pdf {
+                            Some(scatter_pdf) => get_power_heuristic(
+                                scatter_pdf,
+                                self.get_environment_pdf(environment, &direction),
+                            ),
+                            None => 1.0,
+                        };
+                        color = color.plus(
+                            &environment
+                                .get_radiance(&direction)
+                                .filter(&throughput)
+                                .times(weight),
+                        );
+                    }
+                    break;
+                }
+            };
+
+            let object_id = contact.get_object_id();
+            let material = &self.objects[object_id].get_properties().material;
+            if material.is_invisible() {
+                travelled_distance += surface_distance;
+                self.cross_surface(&mut media, &contact);
+                ray = contact.spawn_ray(ray.direction);
+                continue;
+            }
+
+            if let Some(shading_normal) = material.get_shading_normal(&contact) {
+                contact.set_shading_normal(shading_normal);
+            }
+            // The first surface seen, invisible ones are looked through.
+            if pixel_sample.bounce_count == 0 {
+                pixel_sample.albedo = material.get_albedo(&contact);
+                pixel_sample.normal = contact.get_facing_normal();
+                pixel_sample.distance = contact.get_distance_from_origin();
+                pixel_sample.object_id = Some(object_id);
+            }
+            pixel_sample.bounce_count = bounce_count as u32;
+
+            let emission = material.get_emission(&contact);
+            let emission_weight = match previous_pdf {
+                Some(scatter_pdf) => get_power_heuristic(
+                    scatter_pdf,
+                    self.get_emitter_pdf(
+                        object_id,
+                        &contact,
+                        travelled_distance + surface_distance,
+                    )
+                    .unwrap_or(0.0),
+                ),
+                None => 1.0,
+            };
+            color = color.plus(&emission.filter(&throughput).times(emission_weight));
+
+            if material.get_index_of_refraction().is_some() {
+                contact.set_surrounding_index_of_refraction(
+                    media.get_surrounding_index_of_refraction(object_id),
+                );
+            }
+
+            let next_ray = match material.scatter(&contact, sampler) {
+                Some(sample)
+                    if crosses_opaque_surface(material.as_ref(), &contact, &sample.ray) =>
+                {
+                    break
+                }
+                Some(sample) => {
+                    if sample.is_specular {
+                        previous_pdf = None;
                     } else {
-                        let mut color = Color::zero();
-                        if transparency < 1.0 {
-                            let reflection_ray = contact.get_outer_reflection(roughness);
-                            let reflection_color =
-                                self.project_ray(reflection_ray, bounces_left - 1);
-                            color = color.plus(&reflection_color.times(1.0 - transparency));
-                        }
-
-                        if transparency > 0.0 {
-                            let refraction_ray = contact.get_refraction(1.0, density);
-                            let refraction_color =
-                                self.project_ray(refraction_ray, bounces_left - 1);
-                            color = color.plus(&refraction_color.times(transparency));
-                        }
-
-                        color.filter(&properties.color)
+                        color = color.plus(
+                            &self
+                                .sample_emitters(material.as_ref(), &contact, &media, sampler)
+                                .filter(&throughput),
+                        );
+                        previous_pdf = Some(sample.pdf);
                     }
+
+                    throughput = throughput.filter(&sample.attenuation);
+                    sample.ray
                 }
+                None => break,
+            };
+
+            if material.is_transmissive() && contact.is_transmission(&next_ray.direction) {
+                self.cross_surface(&mut media, &contact);
             }
+            travelled_distance = 0.0;
+            ray = next_ray;
+
+            if !self.survives_roulette(bounce_count, &mut throughput, sampler) {
+                break;
+            }
+        }
+
+        pixel_sample.color = color;
+        pixel_sample
+    }
+
+    fn survives_roulette(
+        &self,
+        bounce_count: usize,
+        throughput: &mut Color,
+        sampler: &mut dyn Sampler,
+    ) -> bool {
+        if bounce_count < self.min_bounce_count {
+            return true;
+        }
+
+        // Survivors are scaled up by the probability they had to be dropped, which keeps the
+        // estimate unbiased while dim paths stop early.
+        let survival_probability = throughput.get_max_component().min(1.0);
+        if sampler.get_random_number() >= survival_probability {
+            return false;
+        }
+        *throughput = throughput.times(1.0 / survival_probability);
+        true
+    }
+
+    // Transmitted rays enter the object behind the surface of the contact, or leave the one it
+    // bounds.
+    fn cross_surface(&self, media: &mut MediumStack, contact: &RayContact) {
+        let object_id = contact.get_object_id();
+        if contact.is_from_inside() {
+            media.exit(object_id);
         } else {
-            Color::zero()
+            let properties = self.objects[object_id].get_properties();
+            media.enter(
+                object_id,
+                properties.material.get_index_of_refraction(),
+                properties.medium,
+            );
         }
     }
 
-    fn find_closest_contact<'a>(&self, ray: &'a Ray) -> Option<RayContact<'a>> {
-        let mut closest_contact: Option<RayContact<'a>> = None;
-        let mut closest_contact_distance = f32::MAX;
-        for (object_id, object) in self.objects.iter().enumerate() {
-            if let Some(mut contact) = object.get_contact(&ray) {
-                let contact_distance = contact.get_distance_from_origin();
-                if contact_distance < closest_contact_distance {
-                    contact.set_object_id(object_id);
-                    closest_contact = Some(contact);
-                    closest_contact_distance = contact_distance;
+    // Medium the ray travels through before `distance`, with where along the ray it starts and
+    // stops doing so.
+    fn get_medium_segment<'a>(
+        &'a self,
+        media: &'a MediumStack,
+        ray: &Ray,
+        distance: f32,
+    ) -> Option<(&'a Medium, f32, f32)> {
+        if !media.is_outside() {
+            return media.get_medium().map(|medium| (medium, 0.0, distance));
+        }
+
+        let atmosphere = self.atmosphere.as_ref()?;
+        let (start, end) = atmosphere.get_segment(ray, distance)?;
+        Some((atmosphere.get_medium(), start, end))
+    }
+
+    fn get_transmittance(&self, media: &MediumStack, ray: &Ray, distance: f32) -> Color {
+        match self.get_medium_segment(media, ray, distance) {
+            Some((medium, start, end)) => medium.get_transmittance(end - start),
+            None => Color::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    /*
+     * Follows a shadow ray through the invisible surfaces in its way before `max_distance`,
+     * dimming it by the media it crosses. What's left of the ray then only has to reach the light
+     * before hitting anything else.
+     */
+    fn trace_shadow_ray(&self, ray: Ray, media: &MediumStack, max_distance: f32) -> ShadowPath {
+        let mut path = ShadowPath {
+            ray,
+            media: media.clone(),
+            transmittance: Color::new(1.0, 1.0, 1.0),
+            distance: 0.0,
+        };
+
+        for _ in 0..self.max_bounce_count {
+            let next_ray = match self.find_closest_contact(&path.ray) {
+                Some(contact)
+                    if contact.get_distance_from_origin() < max_distance - path.distance
+                        && self.objects[contact.get_object_id()]
+                            .get_properties()
+                            .material
+                            .is_invisible() =>
+                {
+                    let distance = contact.get_distance_from_origin();
+                    path.transmittance = path.transmittance.filter(&self.get_transmittance(
+                        &path.media,
+                        &path.ray,
+                        distance,
+                    ));
+                    path.distance += distance;
+                    self.cross_surface(&mut path.media, &contact);
+                    contact.spawn_ray(path.ray.direction)
                 }
+                _ => break,
+            };
+            path.ray = next_ray;
+        }
+
+        path
+    }
+
+    /*
+     * Next event estimation: light arriving from a point picked on one of the emitters, from one
+     * of the lights, or from a direction picked on the environment, weighted against the chance
+     * of the material sampling the same direction. The lights, and the environment when it can
+     * be sampled, count as more emitters to pick from.
+     */
+    fn sample_emitters(
+        &self,
+        material: &dyn Material,
+        contact: &RayContact,
+        media: &MediumStack,
+        sampler: &mut dyn Sampler,
+    ) -> Color {
+        let light_count = self.get_light_count();
+        if light_count == 0 {
+            return Color::zero();
+        }
+
+        let light_index =
+            ((sampler.get_random_number() * light_count as f32) as usize).min(light_count - 1);
+        let emitter_count = self.emitter_object_ids.len();
+        if light_index >= emitter_count {
+            return match self.lights.get(light_index - emitter_count) {
+                // The light is picked once in `light_count` times.
+                Some(light) => self
+                    .sample_light(light.as_ref(), material, contact, media)
+                    .times(light_count as f32),
+                None => self.sample_environment(material, contact, media, sampler),
+            };
+        }
+
+        let emitter_id = self.emitter_object_ids[light_index];
+        let emitter_position =
+            match self.objects[emitter_id].sample_surface(sampler.get_random_pair()) {
+                Some(emitter_position) => emitter_position,
+                None => return Color::zero(),
+            };
+
+        let shadow_ray = contact.spawn_ray(emitter_position.minus(&contact.get_position()));
+        if crosses_opaque_surface(material, contact, &shadow_ray) {
+            return Color::zero();
+        }
+        let direction = shadow_ray.direction.normalized();
+        let shadow_distance = shadow_ray.origin.distance_to(&emitter_position);
+        let shadow_path = self.trace_shadow_ray(shadow_ray, media, shadow_distance);
+
+        let emitter_distance = shadow_path.ray.origin.distance_to(&emitter_position);
+        let emitter_contact = match self.find_closest_contact(&shadow_path.ray) {
+            Some(emitter_contact) => emitter_contact,
+            None => return Color::zero(),
+        };
+
+        let is_visible = emitter_contact.get_object_id() == emitter_id
+            && (emitter_contact.get_distance_from_origin() - emitter_distance).abs()
+                <= SHADOW_RAY_TOLERANCE * emitter_distance.max(1.0);
+        if !is_visible {
+            return Color::zero();
+        }
+
+        let emitter_pdf = match self.get_emitter_pdf(
+            emitter_id,
+            &emitter_contact,
+            shadow_path.distance + emitter_contact.get_distance_from_origin(),
+        ) {
+            Some(emitter_pdf) => emitter_pdf,
+            None => return Color::zero(),
+        };
+
+        let scattering = material.evaluate(contact, &direction);
+        let weight = get_power_heuristic(emitter_pdf, material.get_pdf(contact, &direction));
+        let transmittance = shadow_path.get_transmittance(self, emitter_distance);
+
+        let emission = self.objects[emitter_id]
+            .get_properties()
+            .material
+            .get_emission(&emitter_contact);
+        emission
+            .filter(&scattering)
+            .filter(&transmittance)
+            .times(weight / emitter_pdf)
+    }
+
+    // Lights are points or directions that bounces never find, their light is taken as it is.
+    fn sample_light(
+        &self,
+        light: &dyn Light,
+        material: &dyn Material,
+        contact: &RayContact,
+        media: &MediumStack,
+    ) -> Color {
+        let LightSample {
+            direction,
+            distance,
+            irradiance,
+        } = match light.sample(&contact.get_position()) {
+            Some(sample) => sample,
+            None => return Color::zero(),
+        };
+
+        let shadow_ray = contact.spawn_ray(direction);
+        if crosses_opaque_surface(material, contact, &shadow_ray) {
+            return Color::zero();
+        }
+        let shadow_path = self.trace_shadow_ray(shadow_ray, media, distance);
+        let remaining_distance = (distance - shadow_path.distance).max(0.0);
+        if let Some(blocker) = self.find_closest_contact(&shadow_path.ray) {
+            if blocker.get_distance_from_origin() < remaining_distance {
+                return Color::zero();
             }
         }
 
+        irradiance
+            .filter(&material.evaluate(contact, &direction))
+            .filter(&shadow_path.get_transmittance(self, remaining_distance))
+    }
+
+    fn sample_environment(
+        &self,
+        material: &dyn Material,
+        contact: &RayContact,
+        media: &MediumStack,
+        sampler: &mut dyn Sampler,
+    ) -> Color {
+        let environment = match &self.environment {
+            Some(environment) => environment,
+            None => return Color::zero(),
+        };
+        let direction = match environment.sample_direction(sampler.get_random_pair()) {
+            Some((direction, pdf)) if pdf > 0.0 => direction,
+            _ => return Color::zero(),
+        };
+
+        let shadow_ray = contact.spawn_ray(direction);
+        if crosses_opaque_surface(material, contact, &shadow_ray) {
+            return Color::zero();
+        }
+        let shadow_path = self.trace_shadow_ray(shadow_ray, media, f32::INFINITY);
+        if self.find_closest_contact(&shadow_path.ray).is_some() {
+            return Color::zero();
+        }
+
+        // Rounding can leave directions right at the poles of a map without any density.
+        let environment_pdf = self.get_environment_pdf(environment, &direction);
+        if environment_pdf <= 0.0 {
+            return Color::zero();
+        }
+        let scattering = material.evaluate(contact, &direction);
+        let weight = get_power_heuristic(environment_pdf, material.get_pdf(contact, &direction));
+
+        environment
+            .get_radiance(&direction)
+            .filter(&scattering)
+            .filter(&shadow_path.get_transmittance(self, f32::INFINITY))
+            .times(weight / environment_pdf)
+    }
+
+    fn get_light_count(&self) -> usize {
+        let environment_count = match &self.environment {
+            Some(environment) if environment.is_sampled() => 1,
+            _ => 0,
+        };
+        self.emitter_object_ids.len() + self.lights.len() + environment_count
+    }
+
+    // Solid angle density of `sample_emitters` picking `direction` on the environment.
+    fn get_environment_pdf(&self, environment: &Environment, direction: &Vector) -> f32 {
+        if !environment.is_sampled() {
+            return 0.0;
+        }
+
+        environment.get_pdf(direction) / self.get_light_count() as f32
+    }
+
+    // Solid angle density of `sample_emitters` picking the point of `contact` on the object, from
+    // `distance` away.
+    fn get_emitter_pdf(
+        &self,
+        object_id: usize,
+        contact: &RayContact,
+        distance: f32,
+    ) -> Option<f32> {
+        if !self.emitter_object_ids.contains(&object_id) {
+            return None;
+        }
+
+        let area = self.objects[object_id].get_surface_area()?;
+        let cos_emitter = contact
+            .get_incoming_direction()
+            .dot(&contact.get_facing_geometric_normal())
+            .abs();
+        if cos_emitter <= 0.0 {
+            return None;
+        }
+
+        Some(distance * distance / (cos_emitter * area * self.get_light_count() as f32))
+    }
+
+    pub(super) fn find_closest_contact<'a>(&self, ray: &'a Ray) -> Option<RayContact<'a>> {
+        if self.is_bvh_outdated {
+            return self.find_closest_contact_linear(ray);
+        }
+
+        let mut closest_contact: Option<RayContact<'a>> = None;
+        for object_id in &self.unbounded_object_ids {
+            self.update_closest_contact(*object_id, ray, &mut closest_contact);
+        }
+
+        let max_distance = closest_contact
+            .as_ref()
+            .map_or(f32::MAX, |contact| contact.get_distance_from_origin());
+        self.bvh.traverse(ray, max_distance, |item_id| {
+            let object_id = self.bvh_object_ids[item_id];
+            if self.update_closest_contact(object_id, ray, &mut closest_contact) {
+                closest_contact
+                    .as_ref()
+                    .map(|contact| contact.get_distance_from_origin())
+            } else {
+                None
+            }
+        });
+
+        closest_contact
+    }
+
+    pub(super) fn find_closest_contact_linear<'a>(&self, ray: &'a Ray) -> Option<RayContact<'a>> {
+        let mut closest_contact: Option<RayContact<'a>> = None;
+        for object_id in 0..self.objects.len() {
+            self.update_closest_contact(object_id, ray, &mut closest_contact);
+        }
+
         closest_contact
     }
+
+    fn update_closest_contact<'a>(
+        &self,
+        object_id: usize,
+        ray: &'a Ray,
+        closest_contact: &mut Option<RayContact<'a>>,
+    ) -> bool {
+        if let Some(mut contact) = self.objects[object_id].get_contact(ray) {
+            let is_closer = closest_contact.as_ref().map_or(true, |closest| {
+                contact.get_distance_from_origin() < closest.get_distance_from_origin()
+            });
+
+            if is_closer {
+                contact.set_object_id(object_id);
+                *closest_contact = Some(contact);
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Bent shading normals can send light through the surface of materials that don't transmit any.
+fn crosses_opaque_surface(material: &dyn Material, contact: &RayContact, ray: &Ray) -> bool {
+    !material.is_transmissive() && contact.is_transmission(&ray.direction)
+}
+
+fn get_power_heuristic(pdf: f32, other_pdf: f32) -> f32 {
+    let pdf_sqr = pdf * pdf;
+    let sum = pdf_sqr + other_pdf * other_pdf;
+    if sum > 0.0 {
+        pdf_sqr / sum
+    } else {
+        0.0
+    }
 }
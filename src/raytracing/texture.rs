@@ -1,16 +1,22 @@
-use glium::{texture::RawImage2d, Display, Texture2d};
+use std::{
+    borrow::Cow,
+    io,
+    path::{Path, PathBuf},
+};
 
-use crate::primitive::color::Color;
+#[cfg(feature = "window")]
+use glium::{texture::RawImage2d, Display, Texture2d};
 
-/*-----------------------------------------------------------------------------------------------*/
+use crate::primitive::{color::Color, vector::Vector};
 
-pub struct ColorColumnRange {
-    pub starting_column: u32,
-    pub color_columns: Vec<Vec<Color>>,
-}
-
-unsafe impl Send for ColorColumnRange {}
-unsafe impl Sync for ColorColumnRange {}
+use super::{
+    adaptive::{get_heatmap_color, RunningVariance},
+    aov::{get_object_color, get_object_id_value, Aov},
+    denoise::denoise,
+    export::{self, ExrLayer, ImageFormat},
+    tile::{Tile, TileResult},
+    tonemap::ToneMapper,
+};
 
 /*-----------------------------------------------------------------------------------------------*/
 
@@ -19,7 +25,26 @@ pub struct IncrementalTextureHandle {
     height: u32,
     data: Vec<Color>,
     counts: Vec<u32>,
+    luminance_variances: Vec<RunningVariance>,
+    albedos: Vec<Color>,
+    normals: Vec<Vector>,
+    // Mean distance of the samples that hit something, and how many did.
+    distances: Vec<f32>,
+    hit_counts: Vec<u32>,
+    bounce_counts: Vec<f32>,
+    object_ids: Vec<Option<usize>>,
     max_count: u32,
+    tone_mapper: ToneMapper,
+    #[cfg(feature = "window")]
+    shows_heatmap: bool,
+    #[cfg(feature = "window")]
+    shown_aov: Option<Aov>,
+    // Denoising only changes what is shown and saved, `data` keeps accumulating the raw samples.
+    denoises: bool,
+    // Samples accumulated since the last reset, and the denoised image with the count it was made
+    // from. The window reuses it until new samples come in, denoising every frame is too slow.
+    sample_count: u64,
+    denoised: Option<(u64, Vec<Color>)>,
 
     is_invalid: bool,
 }
@@ -37,45 +62,352 @@ impl IncrementalTextureHandle {
             height,
             data,
             counts,
+            luminance_variances: vec![RunningVariance::default(); (width * height) as usize],
+            albedos: vec![Color::zero(); (width * height) as usize],
+            normals: vec![Vector::new(0.0, 0.0, 0.0); (width * height) as usize],
+            distances: vec![0.0; (width * height) as usize],
+            hit_counts: vec![0; (width * height) as usize],
+            bounce_counts: vec![0.0; (width * height) as usize],
+            object_ids: vec![None; (width * height) as usize],
             max_count,
+            tone_mapper: ToneMapper::default(),
+            #[cfg(feature = "window")]
+            shows_heatmap: false,
+            #[cfg(feature = "window")]
+            shown_aov: None,
+            denoises: false,
+            sample_count: 0,
+            denoised: None,
 
             is_invalid: true,
         }
     }
 
-    pub fn add_color(&mut self, x: u32, y: u32, color: &Color) {
+    pub fn add_color_sum(&mut self, x: u32, y: u32, color_sum: &Color, sample_count: u32) {
         let index = (y * self.width + x) as usize;
         let count = self.counts[index];
         if count < self.max_count {
+            let total_count = (count + sample_count) as f32;
             let mut final_color = self.data[index];
-            final_color.r = (final_color.r * count as f32 + color.r) / (count + 1) as f32;
-            final_color.g = (final_color.g * count as f32 + color.g) / (count + 1) as f32;
-            final_color.b = (final_color.b * count as f32 + color.b) / (count + 1) as f32;
+            final_color.r = (final_color.r * count as f32 + color_sum.r) / total_count;
+            final_color.g = (final_color.g * count as f32 + color_sum.g) / total_count;
+            final_color.b = (final_color.b * count as f32 + color_sum.b) / total_count;
             self.data[index] = final_color;
-            self.counts[index] = count + 1;
+            self.counts[index] = count + sample_count;
+            self.sample_count += sample_count as u64;
+        }
+    }
+
+    pub fn add_tile_result(&mut self, result: TileResult) {
+        let tile = result.tile;
+        for i in 0..tile.get_pixel_count() {
+            let x = tile.x + i as u32 % tile.width;
+            let y = tile.y + i as u32 / tile.width;
+            let index = (y * self.width + x) as usize;
+
+            let sums = &result.pixel_sums[i];
+            let count = self.counts[index];
+            if count < self.max_count {
+                let total_count = (count + result.sample_count) as f32;
+                let previous_weight = count as f32 / total_count;
+                self.albedos[index] = self.albedos[index]
+                    .times(previous_weight)
+                    .plus(&sums.albedo.times(1.0 / total_count));
+                self.normals[index] = self.normals[index]
+                    .times(previous_weight)
+                    .plus(&sums.normal.times(1.0 / total_count));
+                self.bounce_counts[index] = self.bounce_counts[index] * previous_weight
+                    + sums.bounce_count as f32 / total_count;
+
+                let hit_count = self.hit_counts[index];
+                if sums.hit_count > 0 {
+                    let total_hit_count = hit_count + sums.hit_count;
+                    self.distances[index] = (self.distances[index] * hit_count as f32
+                        + sums.distance)
+                        / total_hit_count as f32;
+                    self.hit_counts[index] = total_hit_count;
+                }
+                self.object_ids[index] = self.object_ids[index].or(sums.object_id);
+                self.luminance_variances[index].merge(&sums.luminance_variance);
+            }
+
+            self.add_color_sum(x, y, &sums.color, result.sample_count);
+        }
+    }
+
+    #[cfg(feature = "window")]
+    pub fn cycle_aov(&mut self) -> Option<Aov> {
+        self.shown_aov = Aov::next(self.shown_aov);
+        self.shown_aov
+    }
+
+    /*
+     * The output variable as an image. Floating point formats get the raw values: distances,
+     * normals between -1 and 1 and average bounce counts. Otherwise they are scaled to fit between
+     * 0 and 1, with the nearest hits brightest and the pixels that hit nothing black.
+     */
+    fn get_aov_data(&self, aov: Aov, is_normalized: bool) -> Vec<Color> {
+        match aov {
+            Aov::Depth => {
+                let max_distance = self.distances.iter().copied().fold(0.0, f32::max);
+                self.distances
+                    .iter()
+                    .zip(&self.hit_counts)
+                    .map(|(distance, hit_count)| {
+                        let value = if *hit_count == 0 {
+                            0.0
+                        } else if is_normalized {
+                            1.0 - distance / max_distance.max(f32::EPSILON) * 0.9
+                        } else {
+                            *distance
+                        };
+                        Color::new(value, value, value)
+                    })
+                    .collect()
+            }
+            Aov::Normal => self
+                .normals
+                .iter()
+                .map(|normal| {
+                    let normal = if normal.len_sqr() > 0.0 {
+                        normal.normalized()
+                    } else {
+                        *normal
+                    };
+                    if is_normalized && normal.len_sqr() > 0.0 {
+                        Color::new(
+                            0.5 * normal.x + 0.5,
+                            0.5 * normal.y + 0.5,
+                            0.5 * normal.z + 0.5,
+                        )
+                    } else {
+                        Color::new(normal.x, normal.y, normal.z)
+                    }
+                })
+                .collect(),
+            Aov::Albedo => self.albedos.clone(),
+            Aov::ObjectId => self
+                .object_ids
+                .iter()
+                .map(|object_id| get_object_color(*object_id))
+                .collect(),
+            Aov::BounceCount => {
+                let scale = if is_normalized {
+                    1.0 / self.bounce_counts.iter().copied().fold(1.0, f32::max)
+                } else {
+                    1.0
+                };
+                self.bounce_counts
+                    .iter()
+                    .map(|bounce_count| {
+                        let value = bounce_count * scale;
+                        Color::new(value, value, value)
+                    })
+                    .collect()
+            }
         }
     }
 
-    pub fn add_color_range(&mut self, color_range: ColorColumnRange) {
-        for (i, column) in color_range.color_columns.into_iter().enumerate() {
-            self.add_color_column(i as u32 + color_range.starting_column, column);
+    /*
+     * Writes every output variable next to the image. EXR files get them as extra layers of the
+     * same file, with the object ids as plain numbers rather than colors. Other formats get a file
+     * each, named after the image with the variable added before the extension.
+     */
+    pub fn save_aovs(&self, path: &Path, format: ImageFormat) -> io::Result<Vec<PathBuf>> {
+        let is_normalized = !format.is_floating_point();
+        let aov_data: Vec<(Aov, Vec<Color>)> = Aov::ALL
+            .iter()
+            .map(|aov| (*aov, self.get_aov_data(*aov, is_normalized)))
+            .collect();
+
+        if format == ImageFormat::Exr {
+            let object_ids: Vec<u32> = self
+                .object_ids
+                .iter()
+                .map(|object_id| get_object_id_value(*object_id))
+                .collect();
+            let names: Vec<String> = aov_data.iter().map(|(aov, _)| aov.to_string()).collect();
+            let output_data = self.get_output_data();
+            let mut layers = vec![("beauty", ExrLayer::Color(&output_data))];
+            for (name, (aov, data)) in names.iter().zip(&aov_data) {
+                let layer = match aov {
+                    Aov::ObjectId => ExrLayer::Id(&object_ids),
+                    _ => ExrLayer::Color(data),
+                };
+                layers.push((name.as_str(), layer));
+            }
+
+            export::write_exr_layers(path, self.width, self.height, &layers)?;
+            return Ok(vec![path.to_path_buf()]);
         }
+
+        let mut paths = Vec::with_capacity(aov_data.len());
+        for (aov, data) in &aov_data {
+            let aov_path = path.with_extension(format!("{}.{}", aov, format.get_extension()));
+            export::write_image(
+                &aov_path,
+                format,
+                self.width,
+                self.height,
+                data,
+                &ToneMapper::linear(),
+            )?;
+            paths.push(aov_path);
+        }
+
+        Ok(paths)
     }
 
-    pub fn add_color_column(&mut self, x: u32, colors: Vec<Color>) {
-        for (y, color) in colors.iter().enumerate() {
-            self.add_color(x, y as u32, &color);
+    /*
+     * Average relative error of the tile's pixels, infinite while some have too few samples. The
+     * average keeps a few fireflies from holding back a tile that is otherwise converged. Pixels
+     * at the sample cap count as converged, they don't take any more samples.
+     */
+    pub fn get_tile_error(&self, tile: &Tile, min_sample_count: u32) -> f32 {
+        let mut error_sum = 0.0;
+        for y in tile.y..(tile.y + tile.height) {
+            for x in tile.x..(tile.x + tile.width) {
+                let index = (y * self.width + x) as usize;
+                if self.counts[index] >= self.max_count {
+                    continue;
+                }
+
+                let variance = &self.luminance_variances[index];
+                if variance.get_count() < min_sample_count {
+                    return f32::INFINITY;
+                }
+                error_sum += variance.get_relative_error();
+            }
         }
+
+        error_sum / tile.get_pixel_count() as f32
+    }
+
+    pub fn set_denoising(&mut self, denoises: bool) {
+        self.denoises = denoises;
+    }
+
+    #[cfg(feature = "window")]
+    pub fn toggle_denoising(&mut self) -> bool {
+        self.denoises = !self.denoises;
+        self.denoises
+    }
+
+    // The image that is shown and saved, denoised when denoising is on.
+    fn get_output_data(&self) -> Cow<'_, [Color]> {
+        if !self.denoises {
+            Cow::Borrowed(&self.data)
+        } else if let Some(denoised) = self.get_current_denoised() {
+            Cow::Borrowed(denoised)
+        } else {
+            Cow::Owned(self.denoise())
+        }
+    }
+
+    fn denoise(&self) -> Vec<Color> {
+        denoise(
+            self.width,
+            self.height,
+            &self.data,
+            &self.albedos,
+            &self.normals,
+        )
+    }
+
+    fn get_current_denoised(&self) -> Option<&Vec<Color>> {
+        self.denoised
+            .as_ref()
+            .filter(|(sample_count, _)| *sample_count == self.sample_count)
+            .map(|(_, denoised)| denoised)
+    }
+
+    #[cfg(feature = "window")]
+    fn update_denoised(&mut self) {
+        if self.denoises && self.get_current_denoised().is_none() {
+            self.denoised = Some((self.sample_count, self.denoise()));
+        }
+    }
+
+    #[cfg(feature = "window")]
+    pub fn toggle_heatmap(&mut self) -> bool {
+        self.shows_heatmap = !self.shows_heatmap;
+        self.shows_heatmap
+    }
+
+    pub fn save_heatmap(&self, path: &Path, format: ImageFormat) -> io::Result<()> {
+        export::write_image(
+            path,
+            format,
+            self.width,
+            self.height,
+            &self.get_heatmap(),
+            &ToneMapper::default(),
+        )
+    }
+
+    // Sample counts relative to the most sampled pixel.
+    fn get_heatmap(&self) -> Vec<Color> {
+        let max_count = self.counts.iter().copied().max().unwrap_or(0).max(1);
+        self.counts
+            .iter()
+            .map(|count| get_heatmap_color(*count as f32 / max_count as f32))
+            .collect()
+    }
+
+    #[cfg(test)]
+    pub fn get_data(&self) -> &[Color] {
+        &self.data
+    }
+
+    pub fn save(&self, path: &Path, format: ImageFormat) -> io::Result<()> {
+        export::write_image(
+            path,
+            format,
+            self.width,
+            self.height,
+            &self.get_output_data(),
+            &self.tone_mapper,
+        )
+    }
+
+    #[cfg(feature = "window")]
+    pub fn get_tone_mapper(&self) -> ToneMapper {
+        self.tone_mapper
+    }
+
+    pub fn set_tone_mapper(&mut self, tone_mapper: ToneMapper) {
+        self.tone_mapper = tone_mapper;
     }
 
     pub fn invalidate(&mut self) {
         self.is_invalid = true;
     }
 
+    #[cfg(feature = "window")]
     pub fn get_texture(&mut self, display: &Display) -> Texture2d {
+        let mapped_data = if self.shows_heatmap {
+            let tone_mapper = ToneMapper::default();
+            self.get_heatmap()
+                .iter()
+                .map(|color| tone_mapper.map(color))
+                .collect()
+        } else if let Some(aov) = self.shown_aov {
+            let tone_mapper = ToneMapper::linear();
+            self.get_aov_data(aov, true)
+                .iter()
+                .map(|color| tone_mapper.map(color))
+                .collect()
+        } else {
+            self.update_denoised();
+            self.get_output_data()
+                .iter()
+                .map(|color| self.tone_mapper.map(color))
+                .collect()
+        };
+
         let texture = Texture2d::new(
             display,
-            RawImage2d::from_raw_rgb(self.data.clone(), (self.width, self.height)),
+            RawImage2d::from_raw_rgb(mapped_data, (self.width, self.height)),
         )
         .unwrap();
 
@@ -87,10 +419,20 @@ impl IncrementalTextureHandle {
         texture
     }
 
+    #[cfg(feature = "window")]
     fn reset(&mut self) {
         for i in 0..self.data.len() {
             self.data[i as usize] = Color::new(0.0, 0.0, 0.0);
             self.counts[i as usize] = 0;
+            self.luminance_variances[i] = RunningVariance::default();
+            self.albedos[i] = Color::zero();
+            self.normals[i] = Vector::new(0.0, 0.0, 0.0);
+            self.distances[i] = 0.0;
+            self.hit_counts[i] = 0;
+            self.bounce_counts[i] = 0.0;
+            self.object_ids[i] = None;
         }
+        self.sample_count = 0;
+        self.denoised = None;
     }
 }
@@ -0,0 +1,171 @@
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use crate::primitive::{color::Color, vector::Vector};
+
+use super::{adaptive::RunningVariance, scene::PixelSample};
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TileOrder {
+    Spiral,
+    Hilbert,
+    Scanline,
+}
+
+impl FromStr for TileOrder {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "spiral" => Ok(TileOrder::Spiral),
+            "hilbert" => Ok(TileOrder::Hilbert),
+            "scanline" => Ok(TileOrder::Scanline),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for TileOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TileOrder::Spiral => write!(f, "spiral"),
+            TileOrder::Hilbert => write!(f, "hilbert"),
+            TileOrder::Scanline => write!(f, "scanline"),
+        }
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// A block of pixels rendered by a single job. Each tile counts its own samples, the count is also
+// the sample index its next pass starts from.
+#[derive(Clone, Copy, Debug)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub sample_count: u32,
+}
+
+impl Tile {
+    pub fn get_pixel_count(&self) -> usize {
+        (self.width * self.height) as usize
+    }
+}
+
+// What the samples of one pixel added up to during a tile pass. Distances are only summed over
+// the samples that hit something, the object is the first one seen.
+#[derive(Default)]
+pub struct PixelSums {
+    pub color: Color,
+    pub luminance_variance: RunningVariance,
+    pub albedo: Color,
+    pub normal: Vector,
+    pub distance: f32,
+    pub hit_count: u32,
+    pub bounce_count: u32,
+    pub object_id: Option<usize>,
+}
+
+impl PixelSums {
+    pub fn add(&mut self, sample: &PixelSample) {
+        self.color = self.color.plus(&sample.color);
+        self.luminance_variance.add(sample.color.get_luminance());
+        self.albedo = self.albedo.plus(&sample.albedo);
+        self.normal = self.normal.plus(&sample.normal);
+        self.bounce_count += sample.bounce_count;
+
+        if sample.object_id.is_some() {
+            self.distance += sample.distance;
+            self.hit_count += 1;
+            self.object_id = self.object_id.or(sample.object_id);
+        }
+    }
+}
+
+// Sums of the samples a tile pass traced, row by row from the bottom.
+pub struct TileResult {
+    pub tile: Tile,
+    pub sample_count: u32,
+    pub pixel_sums: Vec<PixelSums>,
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+pub fn create_tiles(width: u32, height: u32, tile_size: u32, order: TileOrder) -> Vec<Tile> {
+    let column_count = (width + tile_size - 1) / tile_size;
+    let row_count = (height + tile_size - 1) / tile_size;
+
+    let mut coordinates: Vec<(u32, u32)> = (0..row_count)
+        .flat_map(|row| (0..column_count).map(move |column| (column, row)))
+        .collect();
+
+    match order {
+        TileOrder::Spiral => {
+            // Rings around the center tile, each ring walked by angle.
+            let center = [
+                (column_count as f32 - 1.0) / 2.0,
+                (row_count as f32 - 1.0) / 2.0,
+            ];
+            let get_key = |&(column, row): &(u32, u32)| {
+                let offset = [column as f32 - center[0], row as f32 - center[1]];
+                let ring = offset[0].abs().max(offset[1].abs()).round();
+                (ring, offset[1].atan2(offset[0]))
+            };
+            coordinates.sort_by(|a, b| {
+                let (a_ring, a_angle) = get_key(a);
+                let (b_ring, b_angle) = get_key(b);
+                a_ring
+                    .partial_cmp(&b_ring)
+                    .unwrap_or(Ordering::Equal)
+                    .then(a_angle.partial_cmp(&b_angle).unwrap_or(Ordering::Equal))
+            });
+        }
+        TileOrder::Hilbert => {
+            let grid_size = column_count.max(row_count).next_power_of_two();
+            coordinates.sort_by_key(|&(column, row)| get_hilbert_index(grid_size, column, row));
+        }
+        // The image is stored bottom row first, scanline order starts at the top.
+        TileOrder::Scanline => coordinates.sort_by_key(|&(column, row)| (row_count - row, column)),
+    }
+
+    coordinates
+        .into_iter()
+        .map(|(column, row)| {
+            let x = column * tile_size;
+            let y = row * tile_size;
+            Tile {
+                x,
+                y,
+                width: tile_size.min(width - x),
+                height: tile_size.min(height - y),
+                sample_count: 0,
+            }
+        })
+        .collect()
+}
+
+// Position of a cell along the Hilbert curve filling a `grid_size` square, a power of two.
+fn get_hilbert_index(grid_size: u32, column: u32, row: u32) -> u64 {
+    let (mut x, mut y) = (column, row);
+    let mut index = 0;
+    let mut size = grid_size / 2;
+    while size > 0 {
+        let region_x = (x & size > 0) as u32;
+        let region_y = (y & size > 0) as u32;
+        index += (size as u64) * (size as u64) * ((3 * region_x) ^ region_y) as u64;
+
+        if region_y == 0 {
+            if region_x == 1 {
+                x = grid_size - 1 - x;
+                y = grid_size - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        size /= 2;
+    }
+
+    index
+}
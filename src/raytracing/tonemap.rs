@@ -0,0 +1,145 @@
+use std::{fmt, str::FromStr};
+
+use crate::primitive::color::Color;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToneMapOperator {
+    Clamp,
+    Reinhard,
+    Aces,
+}
+
+impl ToneMapOperator {
+    #[cfg(feature = "window")]
+    pub fn next(self) -> Self {
+        match self {
+            ToneMapOperator::Clamp => ToneMapOperator::Reinhard,
+            ToneMapOperator::Reinhard => ToneMapOperator::Aces,
+            ToneMapOperator::Aces => ToneMapOperator::Clamp,
+        }
+    }
+
+    fn apply(self, color: Color) -> Color {
+        match self {
+            ToneMapOperator::Clamp => color,
+            ToneMapOperator::Reinhard => {
+                // Compressing the luminance instead of each channel keeps saturated highlights
+                // from drifting towards white.
+                let luminance = color.get_luminance();
+                if luminance > 0.0 {
+                    color.times(1.0 / (1.0 + luminance))
+                } else {
+                    color
+                }
+            }
+            ToneMapOperator::Aces => Color::new(
+                get_aces_filmic(color.r),
+                get_aces_filmic(color.g),
+                get_aces_filmic(color.b),
+            ),
+        }
+    }
+}
+
+impl FromStr for ToneMapOperator {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "clamp" => Ok(ToneMapOperator::Clamp),
+            "reinhard" => Ok(ToneMapOperator::Reinhard),
+            "aces" => Ok(ToneMapOperator::Aces),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for ToneMapOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToneMapOperator::Clamp => write!(f, "clamp"),
+            ToneMapOperator::Reinhard => write!(f, "reinhard"),
+            ToneMapOperator::Aces => write!(f, "aces"),
+        }
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputEncoding {
+    Srgb,
+    Gamma(f32),
+}
+
+impl OutputEncoding {
+    fn apply(self, value: f32) -> f32 {
+        match self {
+            OutputEncoding::Srgb => {
+                if value <= 0.0031308 {
+                    12.92 * value
+                } else {
+                    1.055 * value.powf(1.0 / 2.4) - 0.055
+                }
+            }
+            OutputEncoding::Gamma(gamma) => value.powf(1.0 / gamma),
+        }
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Turns the linear radiance accumulated by the integrator into display values between 0 and 1.
+// Exposure is given in stops, so every step of 1.0 doubles the brightness.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ToneMapper {
+    pub operator: ToneMapOperator,
+    pub exposure: f32,
+    pub encoding: OutputEncoding,
+}
+
+impl ToneMapper {
+    pub fn new(operator: ToneMapOperator, exposure: f32, encoding: OutputEncoding) -> Self {
+        Self {
+            operator,
+            exposure,
+            encoding,
+        }
+    }
+
+    // Passes values between 0 and 1 through unchanged, for images that aren't radiance.
+    pub fn linear() -> Self {
+        Self::new(ToneMapOperator::Clamp, 0.0, OutputEncoding::Gamma(1.0))
+    }
+
+    pub fn map(&self, color: &Color) -> Color {
+        let exposed = color.times(2.0f32.powf(self.exposure));
+        let mapped = self.operator.apply(exposed);
+
+        Color::new(
+            self.encode(mapped.r),
+            self.encode(mapped.g),
+            self.encode(mapped.b),
+        )
+    }
+
+    fn encode(&self, value: f32) -> f32 {
+        self.encoding.apply(value.clamp(0.0, 1.0))
+    }
+}
+
+impl Default for ToneMapper {
+    fn default() -> Self {
+        Self::new(ToneMapOperator::Clamp, 0.0, OutputEncoding::Srgb)
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Krzysztof Narkowicz's fit of the ACES reference rendering transform.
+fn get_aces_filmic(value: f32) -> f32 {
+    let value = value.max(0.0);
+    (value * (2.51 * value + 0.03)) / (value * (2.43 * value + 0.59) + 0.14)
+}
@@ -0,0 +1,86 @@
+use super::{
+    get_pixel_seed, independent::IndependentSampler, mix, to_unit_float, Sampler, GOLDEN_GAMMA,
+    ONE_MINUS_EPSILON,
+};
+
+const PRIMES: [u32; 32] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131,
+];
+
+// Digits weighing less than this are lost when the point is converted to a float.
+const MIN_DIGIT_WEIGHT: f64 = 1.0 / (1u32 << 24) as f64;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+/*
+ * Halton sequence, one prime base per dimension. The digits are Owen scrambled with hashes of the
+ * pixel and the digits before them, which breaks up the correlation between the larger bases and
+ * keeps neighbouring pixels from sharing the same pattern. Dimensions past the prime table fall
+ * back to independent random numbers, by then the path has bounced often enough for the pattern
+ * not to matter.
+ */
+pub struct HaltonSampler {
+    pixel_seed: u64,
+    sample_index: u64,
+    dimension: usize,
+    fallback: IndependentSampler,
+}
+
+impl HaltonSampler {
+    pub fn new(seed: u64, pixel_index: u64, sample_index: u64) -> Self {
+        Self {
+            pixel_seed: get_pixel_seed(seed, pixel_index),
+            sample_index,
+            dimension: 0,
+            fallback: IndependentSampler::new(seed, pixel_index, sample_index),
+        }
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn get_random_number(&mut self) -> f32 {
+        if self.dimension >= PRIMES.len() {
+            return to_unit_float(self.fallback.next_u32());
+        }
+
+        let dimension_seed =
+            mix(self.pixel_seed ^ (self.dimension as u64).wrapping_mul(GOLDEN_GAMMA));
+        let point = get_scrambled_radical_inverse(
+            PRIMES[self.dimension],
+            self.sample_index,
+            dimension_seed,
+        );
+        self.dimension += 1;
+
+        point
+    }
+
+    fn get_random_pair(&mut self) -> [f32; 2] {
+        [self.get_random_number(), self.get_random_number()]
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Mirrors the digits of `index` in `base` around the decimal point, shifting every digit by a hash
+// of the digits before it. Runs until the digits drop below float precision, since the scrambled
+// zeros past the end of `index` still contribute.
+fn get_scrambled_radical_inverse(base: u32, index: u64, seed: u64) -> f32 {
+    let base = base as u64;
+    let inverse_base = 1.0 / base as f64;
+
+    let mut remaining = index;
+    let mut reversed_digits = 0;
+    let mut digit_weight = 1.0;
+    while digit_weight > MIN_DIGIT_WEIGHT {
+        let digit = remaining % base;
+        remaining /= base;
+
+        let shift = mix(seed ^ reversed_digits) % base;
+        reversed_digits = reversed_digits * base + (digit + shift) % base;
+        digit_weight *= inverse_base;
+    }
+
+    ((reversed_digits as f64 * digit_weight) as f32).min(ONE_MINUS_EPSILON)
+}
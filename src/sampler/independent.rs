@@ -0,0 +1,50 @@
+use super::{get_pixel_seed, mix, to_unit_float, Sampler, GOLDEN_GAMMA};
+
+// PCG32 multiplier (O'Neill, 2014).
+const PCG_MULTIPLIER: u64 = 6364136223846793005;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+/*
+ * Uniform random numbers. Each pixel draws from its own PCG stream and each sample index starts
+ * at its own position on it, so the image only depends on the seed and the number of samples,
+ * not on which thread rendered what or in which order.
+ */
+pub struct IndependentSampler {
+    state: u64,
+    increment: u64,
+}
+
+impl IndependentSampler {
+    pub fn new(seed: u64, pixel_index: u64, sample_index: u64) -> Self {
+        let stream = get_pixel_seed(seed, pixel_index);
+        let mut sampler = Self {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+
+        sampler.state = mix(stream ^ mix(sample_index.wrapping_mul(GOLDEN_GAMMA)));
+        sampler.next_u32();
+        sampler
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let state = self.state;
+        self.state = state
+            .wrapping_mul(PCG_MULTIPLIER)
+            .wrapping_add(self.increment);
+
+        let xor_shifted = (((state >> 18) ^ state) >> 27) as u32;
+        xor_shifted.rotate_right((state >> 59) as u32)
+    }
+}
+
+impl Sampler for IndependentSampler {
+    fn get_random_number(&mut self) -> f32 {
+        to_unit_float(self.next_u32())
+    }
+
+    fn get_random_pair(&mut self) -> [f32; 2] {
+        [self.get_random_number(), self.get_random_number()]
+    }
+}
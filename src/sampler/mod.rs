@@ -0,0 +1,128 @@
+pub mod halton;
+pub mod independent;
+pub mod sobol;
+pub mod stratified;
+
+use std::{fmt, str::FromStr};
+
+use self::{
+    halton::HaltonSampler, independent::IndependentSampler, sobol::SobolSampler,
+    stratified::StratifiedSampler,
+};
+
+const GOLDEN_GAMMA: u64 = 0x9e3779b97f4a7c15;
+// Largest float below 1.0, samples that could round up to 1.0 are kept under it.
+const ONE_MINUS_EPSILON: f32 = 1.0 - f32::EPSILON / 2.0;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+/*
+ * Numbers in [0, 1) for a single camera sample. Every call hands out the next dimension of the
+ * sample, so the camera and the path have to request them in the same order for every sample of
+ * a pixel for the low discrepancy samplers to spread them well.
+ */
+pub trait Sampler {
+    fn get_random_number(&mut self) -> f32;
+
+    fn get_random_pair(&mut self) -> [f32; 2];
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SamplerKind {
+    Independent,
+    Stratified,
+    Halton,
+    Sobol,
+}
+
+impl SamplerKind {
+    #[cfg(test)]
+    pub const ALL: [SamplerKind; 4] = [
+        SamplerKind::Independent,
+        SamplerKind::Stratified,
+        SamplerKind::Halton,
+        SamplerKind::Sobol,
+    ];
+
+    pub fn create(self, seed: u64, pixel_index: u64, sample_index: u64) -> Box<dyn Sampler> {
+        match self {
+            SamplerKind::Independent => {
+                Box::new(IndependentSampler::new(seed, pixel_index, sample_index))
+            }
+            SamplerKind::Stratified => {
+                Box::new(StratifiedSampler::new(seed, pixel_index, sample_index))
+            }
+            SamplerKind::Halton => Box::new(HaltonSampler::new(seed, pixel_index, sample_index)),
+            SamplerKind::Sobol => Box::new(SobolSampler::new(seed, pixel_index, sample_index)),
+        }
+    }
+}
+
+impl FromStr for SamplerKind {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "independent" => Ok(SamplerKind::Independent),
+            "stratified" => Ok(SamplerKind::Stratified),
+            "halton" => Ok(SamplerKind::Halton),
+            "sobol" => Ok(SamplerKind::Sobol),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for SamplerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SamplerKind::Independent => write!(f, "independent"),
+            SamplerKind::Stratified => write!(f, "stratified"),
+            SamplerKind::Halton => write!(f, "halton"),
+            SamplerKind::Sobol => write!(f, "sobol"),
+        }
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// SplitMix64 finalizer, spreads nearby seeds and indices over unrelated states.
+fn mix(value: u64) -> u64 {
+    let mut value = value.wrapping_add(GOLDEN_GAMMA);
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+    value ^ (value >> 31)
+}
+
+fn get_pixel_seed(seed: u64, pixel_index: u64) -> u64 {
+    mix(seed ^ mix(pixel_index.wrapping_add(GOLDEN_GAMMA)))
+}
+
+// The top 24 bits fill the mantissa exactly, the result stays below 1.0.
+fn to_unit_float(bits: u32) -> f32 {
+    (bits >> 8) as f32 / (1u32 << 24) as f32
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_every_sampler_within_unit_interval() {
+        for kind in SamplerKind::ALL.iter() {
+            for sample_index in 0..256 {
+                let mut sampler = kind.create(7, 12, sample_index);
+                for _ in 0..16 {
+                    let [u, v] = sampler.get_random_pair();
+                    let w = sampler.get_random_number();
+                    for value in &[u, v, w] {
+                        assert!((0.0..1.0).contains(value), "{} gave {}", kind, value);
+                    }
+                }
+            }
+        }
+    }
+}
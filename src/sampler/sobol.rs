@@ -0,0 +1,79 @@
+use super::{get_pixel_seed, mix, to_unit_float, Sampler, GOLDEN_GAMMA};
+
+/*-----------------------------------------------------------------------------------------------*/
+
+/*
+ * Owen-scrambled Sobol points following Burley, "Practical Hash-based Owen Scrambling" (2020).
+ * Every dimension, or pair of dimensions, uses the first two Sobol dimensions with its own
+ * shuffled sample order and its own scrambling, which keeps the 2D stratification of the pairs
+ * without needing direction numbers for higher dimensions.
+ */
+pub struct SobolSampler {
+    pixel_seed: u64,
+    sample_index: u32,
+    dimension: u64,
+}
+
+impl SobolSampler {
+    pub fn new(seed: u64, pixel_index: u64, sample_index: u64) -> Self {
+        Self {
+            pixel_seed: get_pixel_seed(seed, pixel_index),
+            sample_index: sample_index as u32,
+            dimension: 0,
+        }
+    }
+
+    fn next_point(&mut self) -> [u32; 2] {
+        let dimension_seed = mix(self.pixel_seed ^ self.dimension.wrapping_mul(GOLDEN_GAMMA));
+        self.dimension += 1;
+
+        let index = scramble(self.sample_index, dimension_seed as u32);
+        [
+            scramble(index.reverse_bits(), (dimension_seed >> 32) as u32),
+            scramble(
+                get_second_sobol_dimension(index),
+                mix(dimension_seed) as u32,
+            ),
+        ]
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn get_random_number(&mut self) -> f32 {
+        to_unit_float(self.next_point()[0])
+    }
+
+    fn get_random_pair(&mut self) -> [f32; 2] {
+        let [x, y] = self.next_point();
+        [to_unit_float(x), to_unit_float(y)]
+    }
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+// Sobol dimension for the primitive polynomial x + 1, the first dimension is the bit reversal.
+fn get_second_sobol_dimension(index: u32) -> u32 {
+    let mut result = 0;
+    let mut direction = 1 << 31;
+    let mut remaining = index;
+    while remaining != 0 {
+        if remaining & 1 != 0 {
+            result ^= direction;
+        }
+        remaining >>= 1;
+        direction ^= direction >> 1;
+    }
+
+    result
+}
+
+// Nested uniform scrambling, an Owen scramble of all the bits done with a single hash.
+fn scramble(value: u32, seed: u32) -> u32 {
+    let mut value = value.reverse_bits();
+    value = value.wrapping_add(seed);
+    value ^= value.wrapping_mul(0x6c50b47c);
+    value ^= value.wrapping_mul(0xb82f1e52);
+    value ^= value.wrapping_mul(0xc7afe638);
+    value ^= value.wrapping_mul(0x8d22f6e6);
+    value.reverse_bits()
+}
@@ -0,0 +1,76 @@
+use super::{
+    get_pixel_seed, independent::IndependentSampler, mix, to_unit_float, Sampler, GOLDEN_GAMMA,
+    ONE_MINUS_EPSILON,
+};
+
+// Samples are stratified in passes of 16, a 4×4 grid for pairs and 16 intervals for numbers.
+const STRATA_PER_AXIS: u32 = 4;
+const STRATA_COUNT: u32 = STRATA_PER_AXIS * STRATA_PER_AXIS;
+
+/*-----------------------------------------------------------------------------------------------*/
+
+/*
+ * Jittered sampling. Within every pass each sample of a pixel lands in a different stratum of
+ * every dimension, the strata are shuffled independently per dimension so the dimensions don't
+ * line up with each other.
+ */
+pub struct StratifiedSampler {
+    pixel_seed: u64,
+    pass_index: u64,
+    stratum_index: u32,
+    dimension: u64,
+    jitter: IndependentSampler,
+}
+
+impl StratifiedSampler {
+    pub fn new(seed: u64, pixel_index: u64, sample_index: u64) -> Self {
+        Self {
+            pixel_seed: get_pixel_seed(seed, pixel_index),
+            pass_index: sample_index / STRATA_COUNT as u64,
+            stratum_index: (sample_index % STRATA_COUNT as u64) as u32,
+            dimension: 0,
+            jitter: IndependentSampler::new(seed, pixel_index, sample_index),
+        }
+    }
+
+    fn next_stratum(&mut self) -> u32 {
+        let hash = mix(self.pixel_seed
+            ^ mix(self.dimension.wrapping_mul(GOLDEN_GAMMA))
+            ^ self.pass_index.wrapping_mul(0xd1b54a32d192ed03));
+        self.dimension += 1;
+
+        // Xor, multiplication by an odd number and addition are all bijections modulo 16.
+        let flip = hash as u32;
+        let multiplier = ((hash >> 32) as u32) | 1;
+        let offset = (hash >> 48) as u32;
+        ((self.stratum_index ^ flip)
+            .wrapping_mul(multiplier)
+            .wrapping_add(offset))
+            % STRATA_COUNT
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn get_random_number(&mut self) -> f32 {
+        let stratum = self.next_stratum();
+        let jitter = to_unit_float(self.jitter.next_u32());
+
+        // The division can round the top of the last stratum up to 1.0.
+        ((stratum as f32 + jitter) / STRATA_COUNT as f32).min(ONE_MINUS_EPSILON)
+    }
+
+    fn get_random_pair(&mut self) -> [f32; 2] {
+        let stratum = self.next_stratum();
+        let jitter = [
+            to_unit_float(self.jitter.next_u32()),
+            to_unit_float(self.jitter.next_u32()),
+        ];
+
+        [
+            (((stratum % STRATA_PER_AXIS) as f32 + jitter[0]) / STRATA_PER_AXIS as f32)
+                .min(ONE_MINUS_EPSILON),
+            (((stratum / STRATA_PER_AXIS) as f32 + jitter[1]) / STRATA_PER_AXIS as f32)
+                .min(ONE_MINUS_EPSILON),
+        ]
+    }
+}
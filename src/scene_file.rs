@@ -0,0 +1,1559 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::Deserialize;
+
+use crate::{
+    camera::OrbitalCamera,
+    material::{
+        conductor::ConductorMaterial,
+        dielectric::DielectricMaterial,
+        emissive::EmissiveMaterial,
+        invisible::InvisibleMaterial,
+        lambertian::LambertianMaterial,
+        medium::Medium,
+        mirror::MirrorMaterial,
+        normal_map::{BumpMap, NormalMap, NormalMappedMaterial, TangentNormalMap},
+        plastic::PlasticMaterial,
+        texture::{CheckerTexture, GradientTexture, ImageTexture, NoiseTexture, Texture, WrapMode},
+        Material,
+    },
+    object::{
+        box_shape::BoxShape, cone::ConeShape, cylinder::CylinderShape, disk::DiskShape,
+        infinite_plane::InfinitePlaneShape, mesh::TriangleShape, obj::load_obj, plane::PlaneShape,
+        sphere::SphereShape, torus::TorusShape, transformed::TransformedShape, Shape,
+        ShapeProperties,
+    },
+    primitive::{color::Color, transform::Transform, vector::Vector},
+    raytracing::{
+        environment::{
+            Background, ConstantBackground, Environment, GradientBackground, MapBackground,
+            SkyBackground,
+        },
+        light::{DirectionalLight, PointLight, SpotLight},
+        medium::Atmosphere,
+        runner::RaytracingRunner,
+    },
+};
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[derive(Debug)]
+pub enum SceneFileError {
+    Io(PathBuf, io::Error),
+    Parse {
+        path: PathBuf,
+        location: String,
+        error: ron::Error,
+    },
+    InvalidCamera(String),
+    InvalidSettings(String),
+    InvalidEnvironment(String),
+    InvalidAtmosphere(String),
+    InvalidLight {
+        index: usize,
+        message: String,
+    },
+    InvalidShape {
+        name: String,
+        message: String,
+    },
+    InvalidObject {
+        index: usize,
+        name: Option<String>,
+        message: String,
+    },
+}
+
+impl fmt::Display for SceneFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneFileError::Io(path, error) => {
+                write!(f, "could not read {}: {}", path.display(), error)
+            }
+            SceneFileError::Parse {
+                path,
+                location,
+                error,
+            } => {
+                if location.is_empty() || location == "." {
+                    write!(f, "{}: {}", path.display(), error)
+                } else {
+                    write!(f, "{}: at `{}`: {}", path.display(), location, error)
+                }
+            }
+            SceneFileError::InvalidCamera(message) => write!(f, "camera: {}", message),
+            SceneFileError::InvalidSettings(message) => write!(f, "{}", message),
+            SceneFileError::InvalidEnvironment(message) => write!(f, "environment: {}", message),
+            SceneFileError::InvalidAtmosphere(message) => write!(f, "atmosphere: {}", message),
+            SceneFileError::InvalidLight { index, message } => {
+                write!(f, "lights[{}]: {}", index, message)
+            }
+            SceneFileError::InvalidShape { name, message } => {
+                write!(f, "shapes[\"{}\"]: {}", name, message)
+            }
+            SceneFileError::InvalidObject {
+                index,
+                name: Some(name),
+                message,
+            } => write!(f, "objects[{}] (\"{}\"): {}", index, name, message),
+            SceneFileError::InvalidObject {
+                index,
+                name: None,
+                message,
+            } => write!(f, "objects[{}]: {}", index, message),
+        }
+    }
+}
+
+impl Error for SceneFileError {}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CameraDescription {
+    pub focus: Vector,
+    #[serde(default = "default_camera_radius")]
+    pub radius: f32,
+    #[serde(default = "default_camera_aperture")]
+    pub aperture: f32,
+}
+
+// Surface colors that vary over a surface. They take the place of a material's `color`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum TextureDescription {
+    Checker {
+        even: Color,
+        odd: Color,
+        // Squares along each unit of the surface coordinates.
+        #[serde(default = "default_texture_scale")]
+        scale: f32,
+    },
+    Gradient {
+        start: Color,
+        end: Color,
+    },
+    Noise {
+        low: Color,
+        high: Color,
+        // Noise cells per unit of distance.
+        #[serde(default = "default_texture_scale")]
+        scale: f32,
+        #[serde(default = "default_noise_octave_count")]
+        octaves: u32,
+    },
+    Image {
+        path: PathBuf,
+        #[serde(default = "default_wrap_mode")]
+        wrap: WrapMode,
+    },
+}
+
+impl TextureDescription {
+    fn build(&self, base_directory: &Path) -> Result<Arc<dyn Texture>, String> {
+        Ok(match *self {
+            TextureDescription::Checker { even, odd, scale } => {
+                Arc::new(CheckerTexture::new(even, odd, scale))
+            }
+            TextureDescription::Gradient { start, end } => {
+                Arc::new(GradientTexture::new(start, end))
+            }
+            TextureDescription::Noise {
+                low,
+                high,
+                scale,
+                octaves,
+            } => Arc::new(NoiseTexture::new(low, high, scale, octaves)),
+            TextureDescription::Image { ref path, wrap } => {
+                let path = base_directory.join(path);
+                let texture = ImageTexture::load(&path, wrap, true)
+                    .map_err(|error| format!("could not load {}: {}", path.display(), error))?;
+                Arc::new(texture)
+            }
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match *self {
+            TextureDescription::Checker { scale, .. } => check_positive("scale", scale),
+            TextureDescription::Noise { scale, octaves, .. } => {
+                if octaves == 0 {
+                    return Err(String::from("`octaves` must be at least 1"));
+                }
+
+                check_positive("scale", scale)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// Surface detail that bends a material's shading normal without changing its shape.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum NormalMapDescription {
+    // Image of normals in the surface's own frame, as most tools bake them.
+    Normal {
+        path: PathBuf,
+        #[serde(default = "default_wrap_mode")]
+        wrap: WrapMode,
+        #[serde(default = "default_normal_map_strength")]
+        strength: f32,
+    },
+    // Heights from a texture's brightness, `strength` units high at full brightness.
+    Bump {
+        texture: TextureDescription,
+        strength: f32,
+    },
+}
+
+impl NormalMapDescription {
+    fn build(&self, base_directory: &Path) -> Result<Arc<dyn NormalMap>, String> {
+        Ok(match self {
+            NormalMapDescription::Normal {
+                path,
+                wrap,
+                strength,
+            } => {
+                let path = base_directory.join(path);
+                let texture = ImageTexture::load(&path, *wrap, false)
+                    .map_err(|error| format!("could not load {}: {}", path.display(), error))?;
+                Arc::new(TangentNormalMap::new(Arc::new(texture), *strength))
+            }
+            NormalMapDescription::Bump { texture, strength } => {
+                Arc::new(BumpMap::new(texture.build(base_directory)?, *strength))
+            }
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            NormalMapDescription::Normal { strength, .. } => {
+                check_non_negative("strength", *strength)
+            }
+            NormalMapDescription::Bump { texture, .. } => texture
+                .validate()
+                .map_err(|message| format!("texture: {}", message)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum MaterialDescription {
+    Lambertian {
+        #[serde(default = "default_white")]
+        color: Color,
+        #[serde(default)]
+        texture: Option<TextureDescription>,
+        #[serde(default)]
+        normal_map: Option<NormalMapDescription>,
+    },
+    Mirror {
+        #[serde(default = "default_white")]
+        color: Color,
+        #[serde(default)]
+        texture: Option<TextureDescription>,
+        #[serde(default)]
+        normal_map: Option<NormalMapDescription>,
+    },
+    Conductor {
+        #[serde(default = "default_white")]
+        color: Color,
+        #[serde(default)]
+        texture: Option<TextureDescription>,
+        #[serde(default)]
+        normal_map: Option<NormalMapDescription>,
+        roughness: f32,
+    },
+    Plastic {
+        #[serde(default = "default_white")]
+        color: Color,
+        #[serde(default)]
+        texture: Option<TextureDescription>,
+        #[serde(default)]
+        normal_map: Option<NormalMapDescription>,
+        roughness: f32,
+        #[serde(default = "default_plastic_index_of_refraction")]
+        index_of_refraction: f32,
+    },
+    Dielectric {
+        // Tint of the surface, objects with a `medium` tint by depth through it instead.
+        #[serde(default = "default_white")]
+        color: Color,
+        index_of_refraction: f32,
+        #[serde(default)]
+        normal_map: Option<NormalMapDescription>,
+    },
+    Emissive {
+        color: Color,
+        #[serde(default = "default_emissive_intensity")]
+        intensity: f32,
+    },
+    // Lets light straight through, to bound an object's `medium` without a surface of its own.
+    Invisible,
+}
+
+impl MaterialDescription {
+    fn build(&self, base_directory: &Path) -> Result<Arc<dyn Material>, String> {
+        let build_texture = |color: Color, texture: &Option<TextureDescription>| match texture {
+            Some(texture) => texture.build(base_directory),
+            None => Ok(Arc::new(color) as Arc<dyn Texture>),
+        };
+
+        let material: Arc<dyn Material> = match self {
+            MaterialDescription::Lambertian { color, texture, .. } => {
+                Arc::new(LambertianMaterial::new(build_texture(*color, texture)?))
+            }
+            MaterialDescription::Mirror { color, texture, .. } => {
+                Arc::new(MirrorMaterial::new(build_texture(*color, texture)?))
+            }
+            MaterialDescription::Conductor {
+                color,
+                texture,
+                roughness,
+                ..
+            } => Arc::new(ConductorMaterial::new(
+                build_texture(*color, texture)?,
+                *roughness,
+            )),
+            MaterialDescription::Plastic {
+                color,
+                texture,
+                roughness,
+                index_of_refraction,
+                ..
+            } => Arc::new(PlasticMaterial::new(
+                build_texture(*color, texture)?,
+                *roughness,
+                *index_of_refraction,
+            )),
+            MaterialDescription::Dielectric {
+                color,
+                index_of_refraction,
+                ..
+            } => Arc::new(DielectricMaterial::new(*color, *index_of_refraction)),
+            MaterialDescription::Emissive { color, intensity } => {
+                Arc::new(EmissiveMaterial::new(*color, *intensity))
+            }
+            MaterialDescription::Invisible => Arc::new(InvisibleMaterial),
+        };
+
+        Ok(match self.get_normal_map() {
+            Some(normal_map) => Arc::new(NormalMappedMaterial::new(
+                material,
+                normal_map.build(base_directory)?,
+            )),
+            None => material,
+        })
+    }
+
+    fn get_normal_map(&self) -> Option<&NormalMapDescription> {
+        match self {
+            MaterialDescription::Lambertian { normal_map, .. }
+            | MaterialDescription::Mirror { normal_map, .. }
+            | MaterialDescription::Conductor { normal_map, .. }
+            | MaterialDescription::Plastic { normal_map, .. }
+            | MaterialDescription::Dielectric { normal_map, .. } => normal_map.as_ref(),
+            MaterialDescription::Emissive { .. } | MaterialDescription::Invisible => None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(normal_map) = self.get_normal_map() {
+            normal_map
+                .validate()
+                .map_err(|message| format!("normal_map: {}", message))?;
+        }
+
+        match self {
+            MaterialDescription::Lambertian { texture, .. }
+            | MaterialDescription::Mirror { texture, .. } => validate_texture(texture),
+            MaterialDescription::Conductor {
+                texture, roughness, ..
+            } => {
+                validate_texture(texture)?;
+                check_range("roughness", *roughness, 0.0, 1.0)
+            }
+            MaterialDescription::Plastic {
+                texture,
+                roughness,
+                index_of_refraction,
+                ..
+            } => {
+                validate_texture(texture)?;
+                check_range("roughness", *roughness, 0.0, 1.0)?;
+                check_positive("index_of_refraction", *index_of_refraction)
+            }
+            MaterialDescription::Dielectric {
+                index_of_refraction,
+                ..
+            } => check_positive("index_of_refraction", *index_of_refraction),
+            MaterialDescription::Emissive { intensity, .. } => {
+                check_non_negative("intensity", *intensity)
+            }
+            MaterialDescription::Invisible => Ok(()),
+        }
+    }
+}
+
+fn validate_texture(texture: &Option<TextureDescription>) -> Result<(), String> {
+    match texture {
+        Some(texture) => texture
+            .validate()
+            .map_err(|message| format!("texture: {}", message)),
+        None => Ok(()),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum ShapeDescription {
+    Sphere {
+        center: Vector,
+        radius: f32,
+    },
+    Plane {
+        center: Vector,
+        rotation_axis: Vector,
+        rotation_degrees: f32,
+        length: f32,
+        width: f32,
+    },
+    Cube {
+        center: Vector,
+        width: f32,
+        length: f32,
+        height: f32,
+        // Oriented boxes are turned around this axis, the box stays aligned with the world axes
+        // without one.
+        #[serde(default)]
+        rotation_axis: Option<Vector>,
+        #[serde(default)]
+        rotation_degrees: f32,
+        #[serde(default)]
+        inverted: bool,
+    },
+    Disk {
+        center: Vector,
+        normal: Vector,
+        radius: f32,
+    },
+    // Ground that reaches the horizon in every direction.
+    InfinitePlane {
+        point: Vector,
+        normal: Vector,
+    },
+    // Centered on `center`, reaching half the height along the axis to either side.
+    Cylinder {
+        center: Vector,
+        axis: Vector,
+        radius: f32,
+        height: f32,
+        #[serde(default)]
+        open: bool,
+    },
+    // Base around `center`, tip `height` along the axis.
+    Cone {
+        center: Vector,
+        axis: Vector,
+        radius: f32,
+        height: f32,
+        #[serde(default)]
+        open: bool,
+    },
+    Torus {
+        center: Vector,
+        axis: Vector,
+        major_radius: f32,
+        minor_radius: f32,
+    },
+    Triangle {
+        vertices: [Vector; 3],
+        #[serde(default)]
+        normals: Option<[Vector; 3]>,
+    },
+    Mesh {
+        path: PathBuf,
+        #[serde(default)]
+        materials: HashMap<String, MaterialDescription>,
+    },
+    // A copy of one of the scene's shared `shapes`, placed by the object's transform.
+    Instance {
+        shape: String,
+    },
+}
+
+// A built shape, with its own properties when a mesh group names a material of its own.
+struct ShapePart {
+    properties: Option<ShapeProperties>,
+    shape: Arc<dyn Shape>,
+}
+
+impl ShapePart {
+    // Groups only bring their own material, the medium inside is still the object's.
+    fn get_properties(&self, object_properties: &ShapeProperties) -> ShapeProperties {
+        match &self.properties {
+            Some(properties) => ShapeProperties {
+                material: Arc::clone(&properties.material),
+                medium: object_properties.medium,
+            },
+            None => object_properties.clone(),
+        }
+    }
+}
+
+impl ShapeDescription {
+    fn build(&self, base_directory: &Path) -> Result<Vec<ShapePart>, String> {
+        let shape: Arc<dyn Shape> = match self {
+            ShapeDescription::Sphere { center, radius } => {
+                Arc::new(SphereShape::new(*center, *radius))
+            }
+            ShapeDescription::Plane {
+                center,
+                rotation_axis,
+                rotation_degrees,
+                length,
+                width,
+            } => Arc::new(PlaneShape::new(
+                *center,
+                *rotation_axis,
+                rotation_degrees.to_radians(),
+                *length,
+                *width,
+            )),
+            ShapeDescription::Cube {
+                center,
+                width,
+                length,
+                height,
+                rotation_axis,
+                rotation_degrees,
+                inverted,
+            } => {
+                let shape = match rotation_axis {
+                    Some(rotation_axis) => BoxShape::new_oriented(
+                        *center,
+                        *width,
+                        *length,
+                        *height,
+                        *rotation_axis,
+                        rotation_degrees.to_radians(),
+                    ),
+                    None => BoxShape::new(*center, *width, *length, *height),
+                };
+
+                if *inverted {
+                    Arc::new(shape.inverted())
+                } else {
+                    Arc::new(shape)
+                }
+            }
+            ShapeDescription::Disk {
+                center,
+                normal,
+                radius,
+            } => Arc::new(DiskShape::new(*center, *normal, *radius)),
+            ShapeDescription::InfinitePlane { point, normal } => {
+                Arc::new(InfinitePlaneShape::new(*point, *normal))
+            }
+            ShapeDescription::Cylinder {
+                center,
+                axis,
+                radius,
+                height,
+                open,
+            } => Arc::new(CylinderShape::new(*center, *axis, *radius, *height, !*open)),
+            ShapeDescription::Cone {
+                center,
+                axis,
+                radius,
+                height,
+                open,
+            } => Arc::new(ConeShape::new(*center, *axis, *radius, *height, !*open)),
+            ShapeDescription::Torus {
+                center,
+                axis,
+                major_radius,
+                minor_radius,
+            } => Arc::new(TorusShape::new(
+                *center,
+                *axis,
+                *major_radius,
+                *minor_radius,
+            )),
+            ShapeDescription::Triangle { vertices, normals } => {
+                let [a, b, c] = *vertices;
+                match normals {
+                    Some(normals) => Arc::new(TriangleShape::with_normals(a, b, c, *normals)),
+                    None => Arc::new(TriangleShape::new(a, b, c)),
+                }
+            }
+            ShapeDescription::Mesh { path, materials } => {
+                let groups =
+                    load_obj(&base_directory.join(path)).map_err(|error| error.to_string())?;
+
+                let mut group_properties = HashMap::new();
+                for (name, material) in materials {
+                    let material = material
+                        .build(base_directory)
+                        .map_err(|message| format!("material `{}`: {}", name, message))?;
+                    group_properties.insert(
+                        name,
+                        ShapeProperties {
+                            material,
+                            medium: None,
+                        },
+                    );
+                }
+
+                return Ok(groups
+                    .into_iter()
+                    .map(|group| ShapePart {
+                        properties: group
+                            .material_name
+                            .and_then(|name| group_properties.get(&name).cloned()),
+                        shape: Arc::new(group.mesh),
+                    })
+                    .collect());
+            }
+            ShapeDescription::Instance { .. } => {
+                return Err(String::from("an instance can't be built on its own"))
+            }
+        };
+
+        Ok(vec![ShapePart {
+            properties: None,
+            shape,
+        }])
+    }
+}
+
+// One step of an object's placement, the steps are applied in the order they are listed.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum TransformDescription {
+    Translate { offset: Vector },
+    Rotate { axis: Vector, degrees: f32 },
+    Scale { factors: Vector },
+}
+
+impl TransformDescription {
+    fn build(&self) -> Transform {
+        match self {
+            TransformDescription::Translate { offset } => Transform::translation(offset),
+            TransformDescription::Rotate { axis, degrees } => {
+                Transform::rotation(axis, degrees.to_radians())
+            }
+            TransformDescription::Scale { factors } => Transform::scaling(factors),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            TransformDescription::Translate { .. } => Ok(()),
+            TransformDescription::Rotate { axis, .. } => {
+                if axis.len_sqr() == 0.0 {
+                    Err(String::from("rotation `axis` must not be the zero vector"))
+                } else {
+                    Ok(())
+                }
+            }
+            TransformDescription::Scale { factors } => {
+                if factors.x == 0.0 || factors.y == 0.0 || factors.z == 0.0 {
+                    Err(String::from("scale `factors` must not be zero"))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+// What rays that leave the scene see, unlit darkness when there's no environment.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum BackgroundDescription {
+    Constant {
+        color: Color,
+    },
+    Gradient {
+        bottom: Color,
+        top: Color,
+    },
+    Sky {
+        sun_direction: Vector,
+        #[serde(default = "default_sky_turbidity")]
+        turbidity: f32,
+        // Angle from the center of the sun's disk to its edge.
+        #[serde(default = "default_sun_radius_degrees")]
+        sun_radius_degrees: f32,
+    },
+    // Equirectangular .hdr or .exr panorama, with straight up at the top.
+    Map {
+        path: PathBuf,
+    },
+}
+
+impl BackgroundDescription {
+    fn build(&self, base_directory: &Path) -> Result<Box<dyn Background>, String> {
+        Ok(match self {
+            BackgroundDescription::Constant { color } => Box::new(ConstantBackground::new(*color)),
+            BackgroundDescription::Gradient { bottom, top } => {
+                Box::new(GradientBackground::new(*bottom, *top))
+            }
+            BackgroundDescription::Sky {
+                sun_direction,
+                turbidity,
+                sun_radius_degrees,
+            } => Box::new(SkyBackground::new(
+                *sun_direction,
+                *turbidity,
+                sun_radius_degrees.to_radians(),
+            )),
+            BackgroundDescription::Map { path } => {
+                let path = base_directory.join(path);
+                let background = MapBackground::load(&path)
+                    .map_err(|error| format!("could not load {}: {}", path.display(), error))?;
+                Box::new(background)
+            }
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            BackgroundDescription::Sky {
+                sun_direction,
+                turbidity,
+                sun_radius_degrees,
+            } => {
+                check_direction("sun_direction", sun_direction)?;
+                check_range("turbidity", *turbidity, 2.0, 10.0)?;
+                check_range("sun_radius_degrees", *sun_radius_degrees, 0.01, 10.0)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnvironmentDescription {
+    pub background: BackgroundDescription,
+    // Turn around the vertical axis, to bring the sun or a panorama's features around.
+    #[serde(default)]
+    pub rotation_degrees: f32,
+    #[serde(default = "default_environment_intensity")]
+    pub intensity: f32,
+}
+
+// Medium that fills the space outside of the objects, out to `radius` around the origin.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AtmosphereDescription {
+    pub medium: MediumDescription,
+    pub radius: f32,
+}
+
+/*
+ * Homogeneous medium, both coefficients are per unit of distance. Media that only absorb tint
+ * what's seen through them, such as colored glass, scattering ones glow in the light that crosses
+ * them, such as fog or smoke. `asymmetry` goes from -1 for light scattered back to where it came
+ * from, through 0 for light scattered evenly, to 1 for light carrying on.
+ */
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MediumDescription {
+    #[serde(default)]
+    pub absorption: Color,
+    #[serde(default)]
+    pub scattering: Color,
+    #[serde(default)]
+    pub asymmetry: f32,
+}
+
+impl MediumDescription {
+    fn build(&self) -> Medium {
+        Medium::new(self.absorption, self.scattering, self.asymmetry)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        check_non_negative_color("absorption", &self.absorption)?;
+        check_non_negative_color("scattering", &self.scattering)?;
+        check_range("asymmetry", self.asymmetry, -0.99, 0.99)
+    }
+}
+
+// Lights without a shape, `intensity` scales their `color`. Point and spot lights fade with the
+// square of the distance.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum LightDescription {
+    Point {
+        position: Vector,
+        #[serde(default = "default_white")]
+        color: Color,
+        intensity: f32,
+    },
+    // Full strength within `inner_degrees` of `direction`, fading out towards `outer_degrees`.
+    Spot {
+        position: Vector,
+        direction: Vector,
+        #[serde(default = "default_white")]
+        color: Color,
+        intensity: f32,
+        #[serde(default)]
+        inner_degrees: f32,
+        outer_degrees: f32,
+    },
+    // Parallel light shining along `direction`.
+    Directional {
+        direction: Vector,
+        #[serde(default = "default_white")]
+        color: Color,
+        intensity: f32,
+    },
+}
+
+impl LightDescription {
+    fn add_to(&self, runner: &mut RaytracingRunner) {
+        match *self {
+            LightDescription::Point {
+                position,
+                color,
+                intensity,
+            } => runner.add_light(PointLight::new(position, color, intensity)),
+            LightDescription::Spot {
+                position,
+                direction,
+                color,
+                intensity,
+                inner_degrees,
+                outer_degrees,
+            } => runner.add_light(SpotLight::new(
+                position,
+                direction,
+                color,
+                intensity,
+                inner_degrees.to_radians(),
+                outer_degrees.to_radians(),
+            )),
+            LightDescription::Directional {
+                direction,
+                color,
+                intensity,
+            } => runner.add_light(DirectionalLight::new(direction, color, intensity)),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            LightDescription::Point { intensity, .. } => {
+                check_non_negative("intensity", *intensity)
+            }
+            LightDescription::Spot {
+                direction,
+                intensity,
+                inner_degrees,
+                outer_degrees,
+                ..
+            } => {
+                check_direction("direction", direction)?;
+                check_non_negative("intensity", *intensity)?;
+                check_range("outer_degrees", *outer_degrees, 0.0, 180.0)?;
+                check_range("inner_degrees", *inner_degrees, 0.0, *outer_degrees)
+            }
+            LightDescription::Directional {
+                direction,
+                intensity,
+                ..
+            } => {
+                check_direction("direction", direction)?;
+                check_non_negative("intensity", *intensity)
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ObjectDescription {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub material: MaterialDescription,
+    // Fills the inside of closed shapes with a transmissive or invisible material.
+    #[serde(default)]
+    pub medium: Option<MediumDescription>,
+    pub shape: ShapeDescription,
+    #[serde(default)]
+    pub transform: Vec<TransformDescription>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SceneDescription {
+    pub camera: CameraDescription,
+    #[serde(default = "default_min_bounce_count")]
+    pub min_bounce_count: usize,
+    #[serde(default = "default_max_bounce_count")]
+    pub max_bounce_count: usize,
+    // Shapes that objects can place copies of by name, see `ShapeDescription::Instance`.
+    #[serde(default)]
+    pub shapes: HashMap<String, ShapeDescription>,
+    #[serde(default)]
+    pub environment: Option<EnvironmentDescription>,
+    #[serde(default)]
+    pub atmosphere: Option<AtmosphereDescription>,
+    #[serde(default)]
+    pub lights: Vec<LightDescription>,
+    pub objects: Vec<ObjectDescription>,
+
+    #[serde(skip)]
+    base_directory: PathBuf,
+}
+
+impl SceneDescription {
+    pub fn load(path: &Path) -> Result<Self, SceneFileError> {
+        let source = fs::read_to_string(path)
+            .map_err(|error| SceneFileError::Io(path.to_path_buf(), error))?;
+        let mut description =
+            Self::parse(&source).map_err(|(location, error)| SceneFileError::Parse {
+                path: path.to_path_buf(),
+                location,
+                error,
+            })?;
+
+        description.validate()?;
+        description.base_directory = path.parent().map_or_else(PathBuf::new, Path::to_path_buf);
+
+        Ok(description)
+    }
+
+    fn parse(source: &str) -> Result<Self, (String, ron::Error)> {
+        let mut deserializer =
+            ron::Deserializer::from_str(source).map_err(|error| (String::new(), error))?;
+
+        let description: Self = serde_path_to_error::deserialize(&mut deserializer)
+            .map_err(|error| (error.path().to_string(), error.into_inner()))?;
+        deserializer.end().map_err(|error| (String::new(), error))?;
+
+        Ok(description)
+    }
+
+    pub fn build_runner(self, width: u32, height: u32) -> Result<RaytracingRunner, SceneFileError> {
+        let camera = OrbitalCamera::new(
+            width,
+            height,
+            self.camera.focus,
+            self.camera.radius,
+            self.camera.aperture,
+        );
+
+        let mut runner = RaytracingRunner::new(
+            width,
+            height,
+            camera,
+            self.min_bounce_count,
+            self.max_bounce_count,
+        );
+
+        if let Some(environment) = &self.environment {
+            let background = environment
+                .background
+                .build(&self.base_directory)
+                .map_err(SceneFileError::InvalidEnvironment)?;
+            runner.set_environment(Some(Environment::new(
+                background,
+                environment.rotation_degrees.to_radians(),
+                environment.intensity,
+            )));
+        }
+
+        if let Some(atmosphere) = &self.atmosphere {
+            runner.set_atmosphere(Some(Atmosphere::new(
+                atmosphere.medium.build(),
+                atmosphere.radius,
+            )));
+        }
+
+        for light in &self.lights {
+            light.add_to(&mut runner);
+        }
+
+        // Shared shapes are built once, every instance of them holds on to the same geometry.
+        let mut shared_shapes = HashMap::new();
+        for (name, shape) in &self.shapes {
+            let parts = shape.build(&self.base_directory).map_err(|message| {
+                SceneFileError::InvalidShape {
+                    name: name.clone(),
+                    message,
+                }
+            })?;
+            shared_shapes.insert(name.clone(), parts);
+        }
+
+        for (index, object) in self.objects.into_iter().enumerate() {
+            let material = object
+                .material
+                .build(&self.base_directory)
+                .map_err(|message| SceneFileError::InvalidObject {
+                    index,
+                    name: object.name.clone(),
+                    message,
+                })?;
+            let properties = ShapeProperties {
+                material,
+                medium: object.medium.as_ref().map(MediumDescription::build),
+            };
+            let transform = if object.transform.is_empty() {
+                None
+            } else {
+                Some(
+                    object
+                        .transform
+                        .iter()
+                        .fold(Transform::identity(), |transform, step| {
+                            transform.then(&step.build())
+                        }),
+                )
+            };
+
+            if let ShapeDescription::Instance { shape } = &object.shape {
+                // Validation made sure the shape exists.
+                for part in &shared_shapes[shape] {
+                    runner.add_instance(
+                        part.get_properties(&properties),
+                        Arc::clone(&part.shape),
+                        transform.unwrap_or_else(Transform::identity),
+                    );
+                }
+                continue;
+            }
+
+            let name = object.name;
+            let parts = object
+                .shape
+                .build(&self.base_directory)
+                .map_err(|message| SceneFileError::InvalidObject {
+                    index,
+                    name,
+                    message,
+                })?;
+            for part in parts {
+                let properties = part.get_properties(&properties);
+                match transform {
+                    Some(transform) => {
+                        runner.add_object(properties, TransformedShape::new(part.shape, transform))
+                    }
+                    None => runner.add_object(properties, part.shape),
+                }
+            }
+        }
+
+        Ok(runner)
+    }
+
+    fn validate(&self) -> Result<(), SceneFileError> {
+        check_positive("radius", self.camera.radius).map_err(SceneFileError::InvalidCamera)?;
+        check_non_negative("aperture", self.camera.aperture)
+            .map_err(SceneFileError::InvalidCamera)?;
+
+        if self.max_bounce_count == 0 {
+            return Err(SceneFileError::InvalidSettings(String::from(
+                "`max_bounce_count` must be at least 1",
+            )));
+        }
+
+        if self.min_bounce_count > self.max_bounce_count {
+            return Err(SceneFileError::InvalidSettings(format!(
+                "`min_bounce_count` ({}) must not exceed `max_bounce_count` ({})",
+                self.min_bounce_count, self.max_bounce_count
+            )));
+        }
+
+        if let Some(environment) = &self.environment {
+            environment
+                .background
+                .validate()
+                .and_then(|_| check_non_negative("intensity", environment.intensity))
+                .map_err(SceneFileError::InvalidEnvironment)?;
+        }
+
+        if let Some(atmosphere) = &self.atmosphere {
+            atmosphere
+                .medium
+                .validate()
+                .and_then(|_| check_positive("radius", atmosphere.radius))
+                .map_err(SceneFileError::InvalidAtmosphere)?;
+        }
+
+        for (index, light) in self.lights.iter().enumerate() {
+            light
+                .validate()
+                .map_err(|message| SceneFileError::InvalidLight { index, message })?;
+        }
+
+        for (name, shape) in &self.shapes {
+            let result = match shape {
+                ShapeDescription::Instance { .. } => {
+                    Err(String::from("shared shapes can't be instances themselves"))
+                }
+                shape => shape.validate(),
+            };
+            if let Err(message) = result {
+                return Err(SceneFileError::InvalidShape {
+                    name: name.clone(),
+                    message,
+                });
+            }
+        }
+
+        for (index, object) in self.objects.iter().enumerate() {
+            if let Err(message) = object.validate(&self.shapes) {
+                return Err(SceneFileError::InvalidObject {
+                    index,
+                    name: object.name.clone(),
+                    message,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ObjectDescription {
+    fn validate(&self, shared_shapes: &HashMap<String, ShapeDescription>) -> Result<(), String> {
+        self.material.validate()?;
+        if let Some(medium) = &self.medium {
+            medium
+                .validate()
+                .map_err(|message| format!("medium: {}", message))?;
+
+            match &self.material {
+                MaterialDescription::Dielectric { color, .. } => {
+                    if color.r != 1.0 || color.g != 1.0 || color.b != 1.0 {
+                        return Err(String::from(
+                            "a `Dielectric` with a `medium` must keep its `color` white, the \
+                             medium's `absorption` tints it already",
+                        ));
+                    }
+                }
+                MaterialDescription::Invisible => {}
+                // Light never gets through these surfaces, so it would never reach the medium.
+                _ => {
+                    return Err(String::from(
+                        "only a `Dielectric` or `Invisible` material can hold a `medium`",
+                    ))
+                }
+            }
+        }
+        for step in &self.transform {
+            step.validate()?;
+        }
+
+        match &self.shape {
+            ShapeDescription::Instance { shape } if !shared_shapes.contains_key(shape) => {
+                Err(format!("there is no shared shape named `{}`", shape))
+            }
+            shape => shape.validate(),
+        }
+    }
+}
+
+impl ShapeDescription {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            ShapeDescription::Sphere { radius, .. } => check_positive("radius", *radius),
+            ShapeDescription::Plane {
+                rotation_axis,
+                length,
+                width,
+                ..
+            } => {
+                if rotation_axis.len_sqr() == 0.0 {
+                    return Err(String::from("`rotation_axis` must not be the zero vector"));
+                }
+
+                check_positive("length", *length)?;
+                check_positive("width", *width)
+            }
+            ShapeDescription::Cube {
+                width,
+                length,
+                height,
+                rotation_axis,
+                ..
+            } => {
+                if let Some(rotation_axis) = rotation_axis {
+                    if rotation_axis.len_sqr() == 0.0 {
+                        return Err(String::from("`rotation_axis` must not be the zero vector"));
+                    }
+                }
+
+                check_positive("width", *width)?;
+                check_positive("length", *length)?;
+                check_positive("height", *height)
+            }
+            ShapeDescription::Disk { normal, radius, .. } => {
+                check_direction("normal", normal)?;
+                check_positive("radius", *radius)
+            }
+            ShapeDescription::InfinitePlane { normal, .. } => check_direction("normal", normal),
+            ShapeDescription::Cylinder {
+                axis,
+                radius,
+                height,
+                ..
+            }
+            | ShapeDescription::Cone {
+                axis,
+                radius,
+                height,
+                ..
+            } => {
+                check_direction("axis", axis)?;
+                check_positive("radius", *radius)?;
+                check_positive("height", *height)
+            }
+            ShapeDescription::Torus {
+                axis,
+                major_radius,
+                minor_radius,
+                ..
+            } => {
+                check_direction("axis", axis)?;
+                check_positive("minor_radius", *minor_radius)?;
+                if major_radius <= minor_radius {
+                    return Err(format!(
+                        "`major_radius` ({}) must be larger than `minor_radius` ({})",
+                        major_radius, minor_radius
+                    ));
+                }
+
+                Ok(())
+            }
+            ShapeDescription::Triangle { vertices, .. } => {
+                let [a, b, c] = vertices;
+                if b.minus(a).cross(&c.minus(a)).len_sqr() > 0.0 {
+                    Ok(())
+                } else {
+                    Err(String::from("`vertices` must not be collinear"))
+                }
+            }
+            ShapeDescription::Mesh { materials, .. } => {
+                for (name, material) in materials {
+                    material
+                        .validate()
+                        .map_err(|message| format!("material `{}`: {}", name, message))?;
+                }
+
+                Ok(())
+            }
+            ShapeDescription::Instance { .. } => Ok(()),
+        }
+    }
+}
+
+fn check_positive(name: &str, value: f32) -> Result<(), String> {
+    if value > 0.0 {
+        Ok(())
+    } else {
+        Err(format!("`{}` must be positive, got {}", name, value))
+    }
+}
+
+fn check_direction(name: &str, value: &Vector) -> Result<(), String> {
+    if value.len_sqr() > 0.0 {
+        Ok(())
+    } else {
+        Err(format!("`{}` must not be the zero vector", name))
+    }
+}
+
+fn check_non_negative(name: &str, value: f32) -> Result<(), String> {
+    if value >= 0.0 {
+        Ok(())
+    } else {
+        Err(format!("`{}` must not be negative, got {}", name, value))
+    }
+}
+
+fn check_non_negative_color(name: &str, value: &Color) -> Result<(), String> {
+    if value.r >= 0.0 && value.g >= 0.0 && value.b >= 0.0 {
+        Ok(())
+    } else {
+        Err(format!(
+            "`{}` must not have negative components, got ({}, {}, {})",
+            name, value.r, value.g, value.b
+        ))
+    }
+}
+
+fn check_range(name: &str, value: f32, min: f32, max: f32) -> Result<(), String> {
+    if value >= min && value <= max {
+        Ok(())
+    } else {
+        Err(format!(
+            "`{}` must be between {} and {}, got {}",
+            name, min, max, value
+        ))
+    }
+}
+
+fn default_camera_radius() -> f32 {
+    1.0
+}
+
+fn default_camera_aperture() -> f32 {
+    0.02
+}
+
+fn default_plastic_index_of_refraction() -> f32 {
+    1.5
+}
+
+fn default_white() -> Color {
+    Color::new(1.0, 1.0, 1.0)
+}
+
+fn default_texture_scale() -> f32 {
+    1.0
+}
+
+fn default_noise_octave_count() -> u32 {
+    4
+}
+
+fn default_wrap_mode() -> WrapMode {
+    WrapMode::Repeat
+}
+
+fn default_normal_map_strength() -> f32 {
+    1.0
+}
+
+fn default_sky_turbidity() -> f32 {
+    3.0
+}
+
+fn default_sun_radius_degrees() -> f32 {
+    0.5
+}
+
+fn default_environment_intensity() -> f32 {
+    1.0
+}
+
+fn default_emissive_intensity() -> f32 {
+    1.0
+}
+
+fn default_min_bounce_count() -> usize {
+    3
+}
+
+fn default_max_bounce_count() -> usize {
+    64
+}
+
+/*-----------------------------------------------------------------------------------------------*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Loads `objects` in a scene that has nothing else, through a file like the command line does.
+    fn load_objects(name: &str, objects: &str) -> Result<SceneDescription, SceneFileError> {
+        let path = std::env::temp_dir().join(format!(
+            "rustracing-scene-{}-{}.ron",
+            name,
+            std::process::id()
+        ));
+        let source = format!(
+            "(camera: (focus: (x: 0.0, y: 0.0, z: 0.0)), objects: [{}])",
+            objects
+        );
+        fs::write(&path, source).unwrap();
+        let result = SceneDescription::load(&path);
+        fs::remove_file(&path).ok();
+
+        result
+    }
+
+    fn assert_invalid_object(
+        result: Result<SceneDescription, SceneFileError>,
+        expected_index: usize,
+        expected_message: &str,
+    ) {
+        match result {
+            Err(SceneFileError::InvalidObject { index, message, .. }) => {
+                assert_eq!(index, expected_index);
+                assert!(
+                    message.contains(expected_message),
+                    "expected a message about {}, got {}",
+                    expected_message,
+                    message
+                );
+            }
+            Err(error) => panic!("expected an invalid object, got {}", error),
+            Ok(_) => panic!("expected an invalid object"),
+        }
+    }
+
+    #[test]
+    fn loads_small_scene() {
+        let description = load_objects(
+            "small",
+            r#"
+                (
+                    name: Some("ball"),
+                    material: Plastic(color: (r: 0.8, g: 0.2, b: 0.2), roughness: 0.3),
+                    shape: Sphere(center: (x: 0.0, y: 0.0, z: 0.0), radius: 0.5),
+                    transform: [
+                        Scale(factors: (x: 1.0, y: 2.0, z: 1.0)),
+                        Translate(offset: (x: 0.0, y: 1.0, z: 0.0)),
+                    ],
+                ),
+                (
+                    material: Lambertian(),
+                    shape: InfinitePlane(
+                        point: (x: 0.0, y: -1.0, z: 0.0),
+                        normal: (x: 0.0, y: 1.0, z: 0.0),
+                    ),
+                ),
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(description.camera.radius, default_camera_radius());
+        assert_eq!(description.min_bounce_count, default_min_bounce_count());
+        assert_eq!(description.max_bounce_count, default_max_bounce_count());
+        assert_eq!(description.objects.len(), 2);
+
+        let ball = &description.objects[0];
+        assert_eq!(ball.name.as_deref(), Some("ball"));
+        assert!(matches!(
+            ball.material,
+            MaterialDescription::Plastic { roughness, .. } if roughness == 0.3
+        ));
+        assert!(matches!(
+            ball.shape,
+            ShapeDescription::Sphere { radius, .. } if radius == 0.5
+        ));
+        assert_eq!(ball.transform.len(), 2);
+        assert!(description.objects[1].name.is_none());
+
+        description.build_runner(8, 8).unwrap();
+    }
+
+    #[test]
+    fn reports_unknown_field_with_its_path() {
+        let result = load_objects(
+            "unknown-field",
+            r#"
+                (
+                    material: Lambertian(colour: (r: 1.0, g: 1.0, b: 1.0)),
+                    shape: Sphere(center: (x: 0.0, y: 0.0, z: 0.0), radius: 1.0),
+                ),
+            "#,
+        );
+
+        match result {
+            Err(SceneFileError::Parse {
+                location, error, ..
+            }) => {
+                assert_eq!(location, "objects[0].material.Lambertian.colour");
+                assert!(
+                    error.to_string().contains("colour"),
+                    "expected an error about `colour`, got {}",
+                    error
+                );
+            }
+            Err(error) => panic!("expected a parse error, got {}", error),
+            Ok(_) => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn reports_invalid_mesh_material() {
+        // Mesh materials are keyed by the OBJ's material names and are checked before the file is
+        // read, so the mesh doesn't have to exist.
+        let result = load_objects(
+            "mesh-material",
+            r#"
+                (
+                    material: Lambertian(),
+                    shape: Sphere(center: (x: 0.0, y: 0.0, z: 0.0), radius: 1.0),
+                ),
+                (
+                    material: Lambertian(),
+                    shape: Mesh(
+                        path: "missing.obj",
+                        materials: {"red": Plastic(roughness: 1.5)},
+                    ),
+                ),
+            "#,
+        );
+
+        assert_invalid_object(result, 1, "material `red`: `roughness`");
+    }
+
+    #[test]
+    fn reports_invalid_transform() {
+        let result = load_objects(
+            "transform",
+            r#"
+                (
+                    material: Lambertian(),
+                    shape: Sphere(center: (x: 0.0, y: 0.0, z: 0.0), radius: 1.0),
+                    transform: [
+                        Rotate(axis: (x: 0.0, y: 1.0, z: 0.0), degrees: 30.0),
+                        Scale(factors: (x: 1.0, y: 0.0, z: 1.0)),
+                    ],
+                ),
+            "#,
+        );
+
+        assert_invalid_object(result, 0, "scale `factors`");
+    }
+
+    #[test]
+    fn rejects_medium_inside_opaque_material() {
+        let load_with_medium = |material: &str| {
+            load_objects(
+                "medium",
+                &format!(
+                    r#"
+                        (
+                            material: {},
+                            medium: Some((absorption: (r: 1.0, g: 1.0, b: 1.0))),
+                            shape: Sphere(center: (x: 0.0, y: 0.0, z: 0.0), radius: 1.0),
+                        ),
+                    "#,
+                    material
+                ),
+            )
+        };
+
+        for material in &[
+            "Lambertian()",
+            "Mirror()",
+            "Conductor(roughness: 0.2)",
+            "Plastic(roughness: 0.2)",
+            "Emissive(color: (r: 1.0, g: 1.0, b: 1.0))",
+        ] {
+            assert_invalid_object(load_with_medium(material), 0, "can hold a `medium`");
+        }
+
+        load_with_medium("Dielectric(index_of_refraction: 1.5)").unwrap();
+        load_with_medium("Invisible").unwrap();
+    }
+}